@@ -1,6 +1,6 @@
 #!/bin/bash
 # Pell equation benchmark: Egypt vs PARI/GP
-# Simulates auto-doubling precision for fair comparison
+# Egypt solves exactly from the integer CF of √D (PQa), with no precision to choose
 #
 # Usage: ./scripts/bench_pell.sh [D] [runs]
 #   D     - discriminant (default: 4729494 = cattle problem)
@@ -28,21 +28,16 @@ if [ ! -x "$EGYPT" ]; then
     exit 1
 fi
 
-# Egypt with auto-doubling
-egypt_doubling() {
-    local total=0
-    for p in 64 128 256 512 1024 2048 4096 8192; do
-        local start=$(date +%s%N)
-        $EGYPT "$D sqrt" 1 --pell -p $p 2>&1 | grep -q "Fundamental"
-        local found=$?
-        local end=$(date +%s%N)
-        local elapsed=$(( (end - start) / 1000000 ))
-        total=$((total + elapsed))
-        if [ $found -eq 0 ]; then
-            echo $total
-            return 0
-        fi
-    done
+# Egypt with the exact periodic CF of √D (no precision needed)
+egypt_exact() {
+    local start=$(date +%s%N)
+    $EGYPT --pell "$D" 2>&1 | grep -q "Fundamental"
+    local found=$?
+    local end=$(date +%s%N)
+    if [ $found -eq 0 ]; then
+        echo $(( (end - start) / 1000000 ))
+        return 0
+    fi
     echo "FAILED"
     return 1
 }
@@ -64,10 +59,10 @@ pari_regulator() {
 }
 
 # Run benchmarks
-echo "--- Egypt (with auto-doubling) ---"
+echo "--- Egypt (exact PQa) ---"
 egypt_times=()
 for i in $(seq 1 $RUNS); do
-    t=$(egypt_doubling)
+    t=$(egypt_exact)
     egypt_times+=($t)
     echo "Run $i: ${t}ms"
 done
@@ -101,7 +96,7 @@ if [ $PARI_AVAILABLE -eq 1 ]; then
 
     # Summary
     echo "=== Summary ==="
-    echo "Egypt (auto-doubling): ${egypt_avg}ms"
+    echo "Egypt (exact PQa):     ${egypt_avg}ms"
     echo "PARI/GP:              ${pari_avg}ms"
     speedup=$(echo "scale=1; $pari_avg / $egypt_avg" | bc)
     echo "Speedup: ${speedup}x"
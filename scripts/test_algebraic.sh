@@ -0,0 +1,64 @@
+#!/bin/bash
+# Algebraic root test: --algebraic/--root must give the exact CF of the chosen real root,
+# end at rational roots, count roots without multiplicity and reject a missing root
+#
+# Usage: ./scripts/test_algebraic.sh
+#   EGYPT - path to binary (default: ./target/release/egypt)
+
+EGYPT=${EGYPT:-./target/release/egypt}
+
+if [ ! -x "$EGYPT" ]; then
+    echo "Error: $EGYPT not found. Run 'cargo build --release' first."
+    exit 1
+fi
+
+failed=0
+
+# check <expected quotients> <args...>
+check() {
+    local got=$($EGYPT --cf "${@:2}" 2>/dev/null | tr '\n' ' ')
+    if [ "$got" == "$1 " ]; then
+        echo "ok   ${*:2}: $1"
+    else
+        echo "FAIL ${*:2}: got '$got', expected '$1'"
+        failed=1
+    fi
+}
+
+# check_error <expected message> <args...>
+check_error() {
+    local got
+    got=$($EGYPT "${@:2}" 2>&1 >/dev/null)
+    local code=$?
+    if [ $code -eq 2 ] && [[ "$got" == *"$1"* ]]; then
+        echo "ok   ${*:2}: $1"
+    else
+        echo "FAIL ${*:2}: exit code $code, got '$got', expected '$1'"
+        failed=1
+    fi
+}
+
+# ∛2 = [1; 3, 1, 5, 1, 1, 4, ...], the first 4 tuples use 7 quotients
+check "1 3 1 5 1 1 4" --algebraic "1 0 0 -2" --tuples 4
+# x³ - x - 1: the plastic number [1; 3, 12, 1, 1, 3, 2, ...]
+check "1 3 12 1 1 3 2" --algebraic "1 0 -1 -1" --tuples 4
+# the negative root -√2 is expanded as |x|, like every other input
+check "1 2 2 2 2" --algebraic "1 0 -2" --root 1 --tuples 3
+check "1 2 2 2 2" --algebraic "1 0 -2" --root 2 --tuples 3
+
+# Rational roots end
+check "0 2" --algebraic "2 -1"
+check "1 2" --algebraic "2 -5 3" --root 2
+
+# Repeated factors are removed first: (x² - 2)² has two real roots, (x - 1)²(x² - 2) three
+check "1 2 2 2 2" --algebraic "1 0 -4 0 4" --root 2 --tuples 3
+check_error "x^4 - 4x^2 + 4 has 2 real root(s), there is no root 3" --algebraic "1 0 -4 0 4" --root 3
+check "1" --algebraic "1 -2 -1 4 -2" --root 2
+check "1 2 2 2 2" --algebraic "1 -2 -1 4 -2" --root 3 --tuples 3
+
+check_error "x^3 - 2 has 1 real root(s), there is no root 2" --algebraic "1 0 0 -2" --root 2
+check_error "x^2 + 1 has 0 real root(s), there is no root 1" --algebraic "1 0 1"
+check_error "polynomial must have degree 1 or more" --algebraic "0 5"
+check_error "coefficient 'x' is not an integer" --algebraic "1 x"
+
+exit $failed
@@ -0,0 +1,91 @@
+#!/bin/bash
+# Best approximation test: --best-approx (and --coprime) against a brute-force search over all
+# denominators for rationals, and against known convergents for irrationals
+#
+# Usage: ./scripts/test_best_approx.sh
+#   EGYPT - path to binary (default: ./target/release/egypt)
+
+EGYPT=${EGYPT:-./target/release/egypt}
+
+if [ ! -x "$EGYPT" ]; then
+    echo "Error: $EGYPT not found. Run 'cargo build --release' first."
+    exit 1
+fi
+
+failed=0
+
+# best <args...>: the approximation reported on stderr
+best() {
+    $EGYPT "$@" 2>&1 >/dev/null | sed -n 's/^# best approximation: //p'
+}
+
+# brute <n> <d> <Q> <M>: closest p/q to n/d with q <= Q and gcd(q, M) = 1, ties to the smaller q
+brute() {
+    awk -v n="$1" -v d="$2" -v Q="$3" -v M="$4" '
+        function gcd(a, b) { while (b) { t = b; b = a % b; a = t } return a }
+        BEGIN {
+            be = -1
+            for (q = 1; q <= Q; q++) {
+                if (gcd(q, M) != 1) continue
+                p = int(n * q / d)
+                for (c = p; c <= p + 1; c++) {
+                    e = n * q - c * d; if (e < 0) e = -e
+                    # e/(d*q) < be/(d*bq)
+                    if (be < 0 || e * bq < be * q) { be = e; bq = q; bp = c }
+                }
+            }
+            g = gcd(bp, bq); if (bq == 1 || g == 0) g = 1
+            print (bp / g) (bq / g == 1 ? "" : "/" bq / g)
+        }'
+}
+
+# check <n> <d> <Q> [M]
+check() {
+    local m=${4:-1}
+    local args=("$1" "$2" --best-approx "$3")
+    [ -n "$4" ] && args+=(--coprime "$4")
+    local got=$(best "${args[@]}")
+    local expected=$(brute "$1" "$2" "$3" "$m")
+    if [ "$got" == "$expected" ]; then
+        echo "ok   $1/$2 q<=$3${4:+ coprime to $4}: $got"
+    else
+        echo "FAIL $1/$2 q<=$3${4:+ coprime to $4}: got $got, expected $expected"
+        failed=1
+    fi
+}
+
+# check_known <expression> <Q> <expected> [M]
+check_known() {
+    local args=("$1" --best-approx "$2")
+    [ -n "$4" ] && args+=(--coprime "$4")
+    local got=$(best "${args[@]}")
+    if [ "$got" == "$3" ]; then
+        echo "ok   $1 q<=$2${4:+ coprime to $4}: $got"
+    else
+        echo "FAIL $1 q<=$2${4:+ coprime to $4}: got $got, expected $3"
+        failed=1
+    fi
+}
+
+check 7 11 10
+check 7 11 11
+check 7 11 20 11
+check 7 11 50 11
+check 3 7 10 7
+check 649 200 30
+check 649 200 199 10
+check 1000 7 3 7
+check 22 7 100
+check 123 457 60 6
+check 5 12 12 12
+check 2 3 5 3
+
+check_known pi 100 311/99
+check_known pi 1000 355/113
+check_known pi 30000 94053/29938
+check_known "sqrt(2)" 1000 1393/985
+check_known e 1000 1457/536
+check_known phi 100 144/89
+check_known pi 100 289/92 11
+
+exit $failed
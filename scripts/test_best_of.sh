@@ -0,0 +1,70 @@
+#!/bin/bash
+# Portfolio test: --best-of must print the configuration that scores best by its metric,
+# summing exactly to the input, and try the subset merge only once
+#
+# Usage: ./scripts/test_best_of.sh
+#   EGYPT - path to binary (default: ./target/release/egypt)
+
+EGYPT=${EGYPT:-./target/release/egypt}
+
+if [ ! -x "$EGYPT" ]; then
+    echo "Error: $EGYPT not found. Run 'cargo build --release' first."
+    exit 1
+fi
+
+failed=0
+table=$(mktemp)
+trap 'rm -f "$table"' EXIT
+
+# check <metric> <numerator> <denominator>
+check() {
+    local out=$($EGYPT --best-of "$1" "$2" "$3" 2>"$table")
+    local name=$1 errors=""
+    # the starred row scores lowest; the table rows are "# terms<tab>max_den<tab>bits<tab>label"
+    local keys=$(case $1 in terms) echo 1;; max-den) echo 2;; bits) echo 3;; lex) echo "1 2 3";; esac)
+    local best=$(grep -v '^# terms' "$table" | grep '^# [0-9]' | awk -F'\t' -v keys="$keys" '
+        { sub(/^# /, "", $1); n = split(keys, k, " "); key = ""; for (i = 1; i <= n; i++) key = key sprintf("%80s", $(k[i])) }
+        min == "" || key < min { min = key }
+        / \*$/ { starred = key }
+        END { print (starred != "" && starred == min) ? "ok" : "bad" }')
+    [ "$best" == "ok" ] || errors="$errors the starred configuration does not score best;"
+    local rows=$(grep -c -- '--merge subset' "$table")
+    [ "$rows" -eq 1 ] || errors="$errors $rows subset rows;"
+    local terms=$(grep ' \*$' "$table" | cut -f 1 | tr -d '# ')
+    [ "$(echo "$out" | wc -l)" -eq "$terms" ] || errors="$errors output length differs from the starred row;"
+    [ -z "$(echo "$out" | awk -F'\t' '$1 != "1"')" ] || errors="$errors not all unit fractions;"
+    local sum=$($EGYPT --rpn "[ $(echo "$out" | awk -F'\t' '{ printf "1/%s ", $2 }')] sum $2/$3 /" 1 --convergents 2>/dev/null | tail -1 | cut -f 3,4)
+    [ "$sum" == "1	1" ] || errors="$errors sum differs from $2/$3;"
+    if [ -z "$errors" ]; then
+        echo "ok   --best-of $name $2/$3: $terms terms"
+    else
+        echo "FAIL --best-of $name $2/$3:$errors"
+        failed=1
+    fi
+}
+
+for metric in terms max-den bits lex; do
+    for input in "2023 2024" "5 121" "4 17" "65535 65536" "355 113113"; do
+        check $metric $input
+    done
+done
+
+# The README example: 2023/2024 in 5 terms
+got=$($EGYPT --best-of terms 2023 2024 2>/dev/null | cut -f 2 | tr '\n' ' ')
+if [ "$got" == "2 3 8 33 92 " ]; then
+    echo "ok   --best-of terms 2023 2024 = 1/2 + 1/3 + 1/8 + 1/33 + 1/92"
+else
+    echo "FAIL --best-of terms 2023 2024: got '$got'"
+    failed=1
+fi
+
+# The metrics score unit fractions, not raw tuples
+$EGYPT --best-of terms 2023 2024 --raw >/dev/null 2>&1
+if [ $? -eq 2 ]; then
+    echo "ok   --best-of with --raw: rejected"
+else
+    echo "FAIL --best-of with --raw: expected exit code 2"
+    failed=1
+fi
+
+exit $failed
@@ -0,0 +1,55 @@
+#!/bin/bash
+# CF generator test: exact patterns for e, e^(1/n), tanh(1/n) and tan(1/n) must give the same
+# raw tuples as the interval (MPFR) path, which is reached through operations without a pattern
+#
+# Usage: ./scripts/test_cf_generators.sh [tuples]
+#   EGYPT - path to binary (default: ./target/release/egypt)
+
+EGYPT=${EGYPT:-./target/release/egypt}
+TUPLES=${1:-40}
+
+if [ ! -x "$EGYPT" ]; then
+    echo "Error: $EGYPT not found. Run 'cargo build --release' first."
+    exit 1
+fi
+
+failed=0
+
+# check <exact expression> <interval expression>
+check() {
+    local exact=$($EGYPT "$1" --rpn --raw --tuples "$TUPLES" 2>/dev/null)
+    local float=$($EGYPT "$2" --rpn --raw --tuples "$TUPLES" 2>/dev/null)
+    if [ -n "$exact" ] && [ "$exact" == "$float" ]; then
+        echo "ok   $1 = $2 ($TUPLES tuples)"
+    else
+        echo "FAIL $1 differs from $2"
+        failed=1
+    fi
+}
+
+check "e" "e 1/3 ^ 3 ^"
+check "1/2 exp" "e 1/2 ^"
+check "1/7 exp" "e 1/7 ^"
+check "0 1/3 - exp" "e 0 1/3 - ^"
+check "1/3 tanh" "e 2/3 ^ 1 - e 2/3 ^ 1 + /"
+check "1/10 tanh" "e 1/5 ^ 1 - e 1/5 ^ 1 + /"
+check "1 tan" "1 sin 1 cos /"
+check "1/4 tan" "1/4 sin 1/4 cos /"
+
+# Exact expansions stop at 10 tuples by default, like --algebraic, and --cf at the quotients they use
+check_default() {
+    local tuples=$($EGYPT "${@}" --raw 2>/dev/null | wc -l)
+    local quotients=$($EGYPT "${@}" --cf 2>/dev/null | wc -l)
+    if [ "$tuples" -eq 10 ] && [ "$quotients" -eq 19 ]; then
+        echo "ok   $*: 10 tuples, 19 quotients by default"
+    else
+        echo "FAIL $*: $tuples tuples, $quotients quotients by default, expected 10 and 19"
+        failed=1
+    fi
+}
+
+check_default "e"
+check_default "sqrt(2)+sqrt(3)"
+check_default --algebraic "1 0 0 -2"
+
+exit $failed
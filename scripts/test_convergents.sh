@@ -0,0 +1,77 @@
+#!/bin/bash
+# Convergent test: the raw tuples listed by --convergents --with-tuples must be the --raw output
+# (CF-Egypt bijection), and the last convergent of a rational must be the reduced input
+#
+# Usage: ./scripts/test_convergents.sh
+#   EGYPT - path to binary (default: ./target/release/egypt)
+
+EGYPT=${EGYPT:-./target/release/egypt}
+
+if [ ! -x "$EGYPT" ]; then
+    echo "Error: $EGYPT not found. Run 'cargo build --release' first."
+    exit 1
+fi
+
+failed=0
+
+# tuples <args...>: tuple columns of the convergent table, sorted
+tuples() {
+    $EGYPT "$@" --convergents --with-tuples 2>/dev/null | awk -F'\t' 'NR > 1 && NF == 9 { print $6 "\t" $7 "\t" $8 "\t" $9 }' | sort
+}
+
+# check_tuples <args...>
+check_tuples() {
+    local table=$(tuples "$@")
+    local raw=$($EGYPT "$@" --raw 2>/dev/null | sort)
+    if [ -n "$raw" ] && [ "$table" == "$raw" ]; then
+        echo "ok   $*: $(echo "$raw" | wc -l) tuples"
+    else
+        echo "FAIL $*: convergent tuples differ from --raw"
+        failed=1
+    fi
+}
+
+# check_last <numerator> <denominator> <reduced p/q>
+check_last() {
+    local got=$($EGYPT "$1" "$2" --convergents | tail -n 1 | awk -F'\t' '{ print $3 "/" $4 " " $5 }')
+    if [ "$got" == "$3 0" ]; then
+        echo "ok   $1/$2 -> $3"
+    else
+        echo "FAIL $1/$2: last convergent $got, expected $3 0"
+        failed=1
+    fi
+}
+
+# check_cf <expression> <expected quotients, space separated>
+check_cf() {
+    local got=$($EGYPT "$1" --cf --tuples 6 2>/dev/null | head -n $(echo $2 | wc -w) | tr '\n' ' ')
+    if [ "$got" == "$2 " ]; then
+        echo "ok   $1 = [$2 ...]"
+    else
+        echo "FAIL $1: got [$got], expected [$2 ...]"
+        failed=1
+    fi
+}
+
+check_tuples 649 200
+check_tuples 2 3
+check_tuples 1 7
+check_tuples 355 113
+check_tuples 123456789 987654321
+check_tuples "2^89-1" "3^56"
+check_tuples pi -p 128
+check_tuples "sqrt(2)" --tuples 8
+check_tuples e --tuples 8
+check_tuples "sqrt(2)+sqrt(3)" --tuples 6
+
+check_last 649 200 649/200
+check_last 6 4 3/2
+check_last 7 1 7/1
+check_last 1000 7 1000/7
+
+check_cf "sqrt(2)" "1 2 2 2 2"
+check_cf "phi" "1 1 1 1 1"
+check_cf "e" "2 1 2 1 1 4 1 1 6"
+check_cf "pi" "3 7 15 1 292"
+
+exit $failed
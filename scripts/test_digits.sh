@@ -0,0 +1,64 @@
+#!/bin/bash
+# Precision test: --digits N must escalate -p until the printed tuples sum to within 10^-N of the
+# value, and --tuples N until N tuples are certified; the precision used is reported on stderr
+#
+# Usage: ./scripts/test_digits.sh [start bits]
+#   EGYPT - path to binary (default: ./target/release/egypt)
+
+EGYPT=${EGYPT:-./target/release/egypt}
+START=${1:-32}
+
+if [ ! -x "$EGYPT" ]; then
+    echo "Error: $EGYPT not found. Run 'cargo build --release' first."
+    exit 1
+fi
+
+failed=0
+tuples=$(mktemp)
+trap 'rm -f "$tuples"' EXIT
+
+# check_digits <expression> <digits> <exact|interval>
+# The tuples' exact sum S comes from --from-raw; floor(|x - S|·10^N) must be 0
+check_digits() {
+    local bits=$($EGYPT "$1" --digits "$2" --raw -p "$START" 2>&1 >"$tuples" | sed -n 's/^# precision: \([0-9]*\) bits$/\1/p')
+    local sum=$($EGYPT --from-raw < "$tuples" 2>&1 >/dev/null | sed -n 's/^# sum of [0-9]* tuples = \([0-9]*\/[0-9]*\) = .*/\1/p')
+    local error=$($EGYPT --rpn "$1 $sum - 10 $2 ^ *" 1 --cf -p 1024 2>/dev/null | head -1)
+    local errors=""
+    [ -n "$sum" ] && [ "$error" == "0" ] || errors="$errors sum $sum not within 10^-$2;"
+    if [ "$3" == "interval" ]; then
+        [ -n "$bits" ] && [ "$bits" -gt "$START" ] || errors="$errors precision '$bits' not escalated from $START;"
+    else
+        [ -z "$bits" ] || errors="$errors exact input reports precision $bits;"
+    fi
+    if [ -z "$errors" ]; then
+        echo "ok   $1 --digits $2: $(grep -c . "$tuples") tuples${bits:+ at $bits bits}"
+    else
+        echo "FAIL $1 --digits $2:$errors"
+        failed=1
+    fi
+}
+
+# check_tuples <expression> <tuples>
+check_tuples() {
+    local bits=$($EGYPT "$1" --tuples "$2" --raw -p "$START" 2>&1 >"$tuples" | sed -n 's/^# precision: \([0-9]*\) bits$/\1/p')
+    local n=$(grep -c . "$tuples")
+    if [ "$n" -eq "$2" ] && [ -n "$bits" ] && [ "$bits" -gt "$START" ]; then
+        echo "ok   $1 --tuples $2: $n tuples at $bits bits"
+    else
+        echo "FAIL $1 --tuples $2: $n tuples at '$bits' bits"
+        failed=1
+    fi
+}
+
+check_digits pi 20 interval
+check_digits pi 100 interval
+check_digits gamma 30 interval
+check_digits ln2 40 interval
+check_digits "pi e +" 25 interval
+check_digits e 50 exact
+check_digits "2 sqrt 3 sqrt +" 30 exact
+
+check_tuples pi 40
+check_tuples zeta3 20
+
+exit $failed
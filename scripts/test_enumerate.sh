@@ -0,0 +1,41 @@
+#!/bin/bash
+# Enumeration test: compare --enumerate --count against known counts
+#
+# Usage: ./scripts/test_enumerate.sh
+#   EGYPT - path to binary (default: ./target/release/egypt)
+
+EGYPT=${EGYPT:-./target/release/egypt}
+
+if [ ! -x "$EGYPT" ]; then
+    echo "Error: $EGYPT not found. Run 'cargo build --release' first."
+    exit 1
+fi
+
+failed=0
+
+# check <numerator> <denominator> <max terms> <max denominator> <expected count>
+check() {
+    local got=$($EGYPT "$1" "$2" --enumerate "$3" --max-denominator "$4" --count)
+    if [ "$got" == "$5" ]; then
+        echo "ok   $1/$2 terms<=$3 den<=$4: $got"
+    else
+        echo "FAIL $1/$2 terms<=$3 den<=$4: got $got, expected $5"
+        failed=1
+    fi
+}
+
+# 1 as a sum of n distinct unit fractions (OEIS A006585: 1, 0, 1, 6, 72),
+# counted cumulatively; 1806 is the largest denominator for n <= 5
+check 1 1 1 1806 1
+check 1 1 2 1806 1
+check 1 1 3 1806 2
+check 1 1 4 1806 8
+check 1 1 5 1806 80
+
+# Small fractions, counts verified by brute force over all subsets
+check 4 5 3 100 2
+check 3 7 3 60 3
+check 2 3 2 50 1
+check 5 6 3 40 2
+
+exit $failed
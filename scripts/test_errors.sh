@@ -0,0 +1,54 @@
+#!/bin/bash
+# Error test: invalid expressions and arguments must fail with exit code 2 and a message, never panic
+#
+# Usage: ./scripts/test_errors.sh
+#   EGYPT - path to binary (default: ./target/release/egypt)
+
+EGYPT=${EGYPT:-./target/release/egypt}
+
+if [ ! -x "$EGYPT" ]; then
+    echo "Error: $EGYPT not found. Run 'cargo build --release' first."
+    exit 1
+fi
+
+failed=0
+
+# check <expected message> <args...>
+check() {
+    local got
+    got=$($EGYPT "${@:2}" 2>&1 >/dev/null)
+    local code=$?
+    if [ $code -eq 2 ] && [[ "$got" == *"$1"* ]]; then
+        echo "ok   ${*:2}: $1"
+    else
+        echo "FAIL ${*:2}: exit code $code, got '$got', expected '$1'"
+        failed=1
+    fi
+}
+
+check "division by zero" "0 -1 ^" 1
+check "division by zero" "0 -1/2 ^" 1
+check "division by zero" "0^(-1/2)" 1
+check "division by zero" "1 0 /" 1
+
+# Infix errors are located in the expression as typed, not in the RPN it compiles to
+check "below zero (column 5," "2 + sqrt(-1)" 1
+check "unknown token (column 5, stack depth 2); did you mean 'fib'?" "2 + fbi(3)" 1
+check "'^': division by zero (column 2," "0^(-1/2)" 1
+check "'fbi': unknown token (column 4," "10 fbi" 1
+
+# Arguments a library call cannot take are reported, not passed on
+check "prime index must be ≥ 1, got 0" "0 p" 1
+check "prime index must be ≥ 1, got 0" "p(0)" 1
+check "no prime below 2" "2 pp" 1
+
+# An exact combination with a rational value never settles, also when approximated
+check "no quotient could be decided" "(sqrt(2)+sqrt(3))*(sqrt(3)-sqrt(2))" 1
+check "no quotient could be decided" "(sqrt(2)+sqrt(3))*(sqrt(3)-sqrt(2))" 1 --best-approx 10
+
+# Variable names must be identifiers that don't shadow a word
+check "variable name must start with a letter or '_' (column 3, stack depth 1); did you mean '_2'?" "1 2 =" 1
+check "cannot assign to the built-in word 'sqrt'" "5 sqrt = 1" 1
+check "cannot assign to the built-in word 'PI'" "5 PI = 1" 1
+
+exit $failed
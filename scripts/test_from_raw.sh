@@ -0,0 +1,63 @@
+#!/bin/bash
+# Round-trip test: --raw output read back with --from-raw must sum to the input, be reported as
+# canonical and give the same tuples again; bisected tuples keep the sum but are not canonical
+#
+# Usage: ./scripts/test_from_raw.sh
+#   EGYPT - path to binary (default: ./target/release/egypt)
+
+EGYPT=${EGYPT:-./target/release/egypt}
+
+if [ ! -x "$EGYPT" ]; then
+    echo "Error: $EGYPT not found. Run 'cargo build --release' first."
+    exit 1
+fi
+
+failed=0
+
+# sum_of <tuples>: the reduced sum reported by --from-raw
+sum_of() {
+    echo "$1" | $EGYPT --from-raw 2>&1 >/dev/null | sed -n 's/^# sum of [0-9]* tuples = \([^ ]*\) .*/\1/p'
+}
+
+# check <args...>: round trip of the --raw output
+check() {
+    local raw=$($EGYPT "$@" --raw 2>/dev/null)
+    local again=$(echo "$raw" | $EGYPT --from-raw --raw 2>/dev/null)
+    local status=$?
+    if [ $status -eq 0 ] && [ "$(echo "$raw" | sort)" == "$(echo "$again" | sort)" ]; then
+        echo "ok   $*: $(echo "$raw" | wc -l) tuples, sum $(sum_of "$raw")"
+    else
+        echo "FAIL $*: round trip gave status $status"
+        failed=1
+    fi
+}
+
+# check_sum <numerator> <denominator> <reduced sum> <extra args...>: non-canonical tuples keep the sum
+check_sum() {
+    local raw=$($EGYPT "$1" "$2" --raw "${@:4}" 2>/dev/null)
+    local got=$(sum_of "$raw")
+    echo "$raw" | $EGYPT --from-raw -s 2>/dev/null
+    local status=$?
+    if [ "$got" == "$3" ] && [ $status -eq 1 ]; then
+        echo "ok   $1/$2 ${*:4}: sum $got, not canonical"
+    else
+        echo "FAIL $1/$2 ${*:4}: sum $got (expected $3), status $status (expected 1)"
+        failed=1
+    fi
+}
+
+check 649 200
+check 2 3
+check 1 7
+check 7 1
+check 355 113
+check 123456789 987654321
+check "2^89-1" "3^56"
+check pi -p 128
+check "sqrt(2)" --tuples 8
+check e --tuples 8
+
+check_sum 649 200 649/200 --bisect -l 2
+check_sum 2023 2024 2023/2024 --bisect -l 2
+
+exit $failed
@@ -0,0 +1,106 @@
+#!/bin/bash
+# Input test: a fraction typed as one expression must give the same output as numerator and
+# denominator given separately, and infix must follow the usual precedence and match its RPN
+#
+# Usage: ./scripts/test_input.sh
+#   EGYPT - path to binary (default: ./target/release/egypt)
+
+EGYPT=${EGYPT:-./target/release/egypt}
+
+if [ ! -x "$EGYPT" ]; then
+    echo "Error: $EGYPT not found. Run 'cargo build --release' first."
+    exit 1
+fi
+
+failed=0
+
+# same <arguments A> <arguments B>: both inputs give identical output
+same() {
+    local a=$($EGYPT $1 2>&1)
+    local b=$($EGYPT $2 2>&1)
+    if [ -n "$a" ] && [ "$a" == "$b" ]; then
+        echo "ok   '$1' = '$2'"
+    else
+        echo "FAIL '$1' gives '$a', '$2' gives '$b'"
+        failed=1
+    fi
+}
+
+# check <expected output, lines joined by spaces> <args...>
+check() {
+    local got=$($EGYPT "${@:2}" 2>&1 | tr '\t\n' '  ')
+    if [ "$got" == "$1 " ]; then
+        echo "ok   ${*:2}: $1"
+    else
+        echo "FAIL ${*:2}: got '$got', expected '$1'"
+        failed=1
+    fi
+}
+
+# same_rpn <infix expression> <RPN expression> [args...]: both give identical output
+same_rpn() {
+    local a=$($EGYPT --infix "$1" "${@:3}" 2>&1)
+    local b=$($EGYPT --rpn "$2" "${@:3}" 2>&1)
+    local auto=$($EGYPT "$1" "${@:3}" 2>&1)
+    if [ -n "$a" ] && [ "$a" == "$b" ] && [ "$a" == "$auto" ]; then
+        echo "ok   '$1' = '$2'"
+    else
+        echo "FAIL '$1' gives '$a' ('$auto' auto-detected), '$2' gives '$b'"
+        failed=1
+    fi
+}
+
+# check_value <infix expression> <expected p/q>: the exact value is the last convergent of the input
+# (its absolute value, so signs are checked by adding a positive number)
+check_value() {
+    local got=$($EGYPT --convergents -- "$1" 1 2>/dev/null | tail -1 | awk -F'\t' '$5 == "0" { print $3 "/" $4 }')
+    if [ "$got" == "$2" ]; then
+        echo "ok   $1 = $2"
+    else
+        echo "FAIL $1: got '$got', expected $2"
+        failed=1
+    fi
+}
+
+# The denominator is optional: a fraction literal or a division describes the whole input
+check "1 3 1 15 1 35" 3/7
+check "1 3 1 15 1 35" 3 7
+same "3/7" "3 7"
+same "6/14" "3 7"
+same "355/113" "355 113"
+same "355/113 --raw" "355 113 --raw"
+same "3/7 2" "3 14"
+check "1 3" "1 3 /"
+check "1 3 1 15 1 35" "7 inv 3 *"
+check "1 791" "22/7 355/113 -"
+
+# --batch lines may leave out the denominator too
+got=$(printf '3/7\n3\t7\n6/14\t1\n' | $EGYPT --batch 2>&1 | sort -u | tr '\t' ' ')
+if [ "$got" == "3 7 0 3 15 35" ]; then
+    echo "ok   --batch: 3/7, 3<tab>7 and 6/14<tab>1 agree"
+else
+    echo "FAIL --batch: got '$got'"
+    failed=1
+fi
+
+# Infix precedence: unary minus binds looser than ^, ^ binds right, * before +, - is left associative
+check_value "-2^2 + 5" "1/1"
+check_value "5 + -2^2" "1/1"
+check_value "(-2)^2 + 5" "9/1"
+check_value "2^3^2" "512/1"
+check_value "(2^3)^2" "64/1"
+check_value "2 + 3 * 4" "14/1"
+check_value "(2 + 3) * 4" "20/1"
+check_value "10 - 4 - 3" "3/1"
+check_value "2*-3+7" "1/1"
+check_value "-(3 - 5) * 2" "4/1"
+check_value "2^-1" "1/2"
+
+# Infix compiles to the same evaluation as RPN, in one argument or split over two
+same_rpn "(pi+e)/4" "pi e + 4 /" --raw --tuples 5
+same_rpn "fib(100)/fib(101)" "100 fib 101 fib /"
+same_rpn "sqrt(13)" "13 sqrt" --raw
+same_rpn "2^9689 - 1" "2 9689 ^ 1 -" --cf
+same "(pi+e)/4 --raw --tuples 5" "pi+e 4 --raw --tuples 5"
+
+exit $failed
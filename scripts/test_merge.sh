@@ -0,0 +1,69 @@
+#!/bin/bash
+# Merge strategy test: every --merge-strategy must sum exactly to the input with distinct
+# denominators, and optimal and subset must be no longer than greedy (subset no longer than optimal)
+#
+# Usage: ./scripts/test_merge.sh
+#   EGYPT - path to binary (default: ./target/release/egypt)
+
+EGYPT=${EGYPT:-./target/release/egypt}
+
+if [ ! -x "$EGYPT" ]; then
+    echo "Error: $EGYPT not found. Run 'cargo build --release' first."
+    exit 1
+fi
+
+failed=0
+
+fail() {
+    echo "FAIL $1"
+    failed=1
+}
+
+# check <numerator> <denominator> <limit>
+check() {
+    local expected=$($EGYPT "$1" "$2" --cf | tr '\n' ' ')
+    local plain=$($EGYPT "$1" "$2" -l "$3" | wc -l)
+    local counts=()
+    for strategy in greedy optimal subset; do
+        local out=$($EGYPT "$1" "$2" -l "$3" --merge --merge-strategy $strategy 2>/dev/null)
+        local label="$1/$2 -l $3 $strategy"
+        # exact sum: the CF of the sum of the unit fractions equals the CF of the input
+        local sum=$(echo "$out" | awk '{ printf "%s %s / ", $1, $2 }')
+        local got=$($EGYPT --rpn "[ $sum] sum" 1 --cf | tr '\n' ' ')
+        [ "$got" == "$expected" ] || fail "$label: sum has CF '$got', expected '$expected'"
+        echo "$out" | awk '$1 != 1 { bad = 1 } END { exit bad }' || fail "$label: not all unit fractions"
+        [ "$(echo "$out" | cut -f 2 | sort | uniq -d)" == "" ] || fail "$label: repeated denominator"
+        counts+=($(echo "$out" | wc -l))
+    done
+    local greedy=${counts[0]} optimal=${counts[1]} subset=${counts[2]}
+    [ "$greedy" -le "$plain" ] || fail "$1/$2 -l $3: greedy $greedy terms, unmerged $plain"
+    [ "$optimal" -le "$greedy" ] || fail "$1/$2 -l $3: optimal $optimal terms, greedy $greedy"
+    [ "$subset" -le "$optimal" ] || fail "$1/$2 -l $3: subset $subset terms, optimal $optimal"
+    echo "ok   $1/$2 -l $3: $plain unmerged, greedy $greedy, optimal $optimal, subset $subset"
+}
+
+for x in "7 19" "5 121" "4 17" "99 100" "31 311" "2023 2024" "123 1234" "9999 10000" "1001 1002" \
+         "314159 1000000" "12345 12346" "65535 65536"; do
+    for limit in 2 8 64; do
+        check $x $limit
+    done
+done
+
+# The README example: 2023/2024 merges to 17 terms greedily and 12 with subsets
+greedy=$($EGYPT 2023 2024 --merge | wc -l)
+subset=$($EGYPT 2023 2024 --merge --merge-strategy subset | wc -l)
+if [ "$greedy" -eq 17 ] && [ "$subset" -eq 12 ]; then
+    echo "ok   2023/2024: greedy 17, subset 12"
+else
+    fail "2023/2024: greedy $greedy, subset $subset, expected 17 and 12"
+fi
+
+# More than 18 terms: subset falls back to the optimal contiguous merge
+note=$($EGYPT 65535 65536 --merge --merge-strategy subset 2>&1 >/dev/null)
+if [[ "$note" == *"using optimal contiguous merge"* ]]; then
+    echo "ok   65535/65536: subset falls back to optimal"
+else
+    fail "65535/65536: expected the subset fallback note, got '$note'"
+fi
+
+exit $failed
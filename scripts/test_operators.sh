@@ -0,0 +1,90 @@
+#!/bin/bash
+# Operator test: the number-theory words and the numeric literal forms must give known exact
+# values, in RPN and infix, and reject arguments outside their domain with exit code 2
+#
+# Usage: ./scripts/test_operators.sh
+#   EGYPT - path to binary (default: ./target/release/egypt)
+
+EGYPT=${EGYPT:-./target/release/egypt}
+
+if [ ! -x "$EGYPT" ]; then
+    echo "Error: $EGYPT not found. Run 'cargo build --release' first."
+    exit 1
+fi
+
+failed=0
+
+# check <expression> <expected p/q>: the exact value is the last convergent of the input
+# (its absolute value, so signs are checked by adding 1)
+check() {
+    local got=$($EGYPT "$1" 1 --convergents 2>/dev/null | tail -1 | awk -F'\t' '$5 == "0" { print $3 "/" $4 }')
+    if [ "$got" == "$2" ]; then
+        echo "ok   $1 = $2"
+    else
+        echo "FAIL $1: got '$got', expected $2"
+        failed=1
+    fi
+}
+
+# check_error <expected message> <expression>
+check_error() {
+    local got
+    got=$($EGYPT "$2" 1 2>&1 >/dev/null)
+    local code=$?
+    if [ $code -eq 2 ] && [[ "$got" == *"$1"* ]]; then
+        echo "ok   $2: $1"
+    else
+        echo "FAIL $2: exit code $code, got '$got', expected '$1'"
+        failed=1
+    fi
+}
+
+check "100 H" "14466636279520351160221518043104131447711/2788815009188499086581352357412492142272"
+check "100 harmonic" "14466636279520351160221518043104131447711/2788815009188499086581352357412492142272"
+check "10 3 binom" "120/1"
+check "2 10 7 powmod" "2/1"
+check "1 12 bernoulli +" "2039/2730"
+check "1 3 bernoulli +" "1/1"
+check "7 !!" "105/1"
+check "10 primorial" "210/1"
+check "5 catnum" "42/1"
+check "10 lucas" "123/1"
+check "17 5 mod" "2/1"
+check "0 17 - 5 mod" "3/1"
+check "1 0 17 - 5 div +" "3/1"
+check "[ 12 18 30 ] gcd" "6/1"
+check "[ 4 6 ] lcm" "12/1"
+check "6/4 numer" "3/1"
+check "6/4 denom" "2/1"
+check "3 0 1 - 7 powmod" "5/1"
+
+# The same words as infix calls and postfix operators
+check "binom(10, 3)" "120/1"
+check "powmod(2, 10, 7)" "2/1"
+check "7!! + 5!" "225/1"
+check "gcd(12, 18, 30)" "6/1"
+check "1 + bernoulli(12)" "2039/2730"
+
+# Literals are exact, and a repeating part in parentheses stays part of the literal in infix
+check "0.1" "1/10"
+check "0.(142857)" "1/7"
+check "0.1(6)" "1/6"
+check "1.5e-3" "3/2000"
+check "1.5E+2" "150/1"
+check "0xff" "255/1"
+check "0b101" "5/1"
+check "0o17" "15/1"
+check "1 -0.25 +" "3/4"
+check "0.(3) + 1" "4/3"
+check "2*0.(3)" "2/3"
+check "(0.(3))" "1/3"
+
+check_error "literal out of range" "1e999999999"
+check_error "literal out of range" "1 + 1e-100001"
+check_error "division by zero" "2 10 0 powmod"
+check_error "base not invertible" "2 0 1 - 4 powmod"
+check_error "division by zero" "5 0 mod"
+check_error "division by zero" "5 0 div"
+check_error "expected an exact value" "pi numer"
+
+exit $failed
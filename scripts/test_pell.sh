@@ -0,0 +1,65 @@
+#!/bin/bash
+# Pell equation test: --pell D against known fundamental solutions of p² - D·q² = 1,
+# including the cattle problem, the legacy "D sqrt" --pell form and the tuple columns
+#
+# Usage: ./scripts/test_pell.sh
+#   EGYPT - path to binary (default: ./target/release/egypt)
+
+EGYPT=${EGYPT:-./target/release/egypt}
+
+if [ ! -x "$EGYPT" ]; then
+    echo "Error: $EGYPT not found. Run 'cargo build --release' first."
+    exit 1
+fi
+
+failed=0
+
+# check <expected p> <expected q> <args...>
+check() {
+    local expected="$2	$1	1"
+    local got=$($EGYPT "${@:3}" 2>/dev/null | tail -n 1 | cut -f 1-3)
+    if [ "$got" == "$expected" ]; then
+        echo "ok   ${*:3}: p=$1, q=$2"
+    else
+        echo "FAIL ${*:3}: got '$got', expected '$expected'"
+        failed=1
+    fi
+}
+
+check 3 2 --pell 2
+check 2 1 --pell 3
+check 9 4 --pell 5
+check 8 3 --pell 7
+check 649 180 --pell 13
+check 649 180 "13 sqrt" 1 --pell
+check 649 180 "sqrt(13)" --pell
+check 649 180 --pell "10+3"
+check 1766319049 226153980 --pell 61
+check 158070671986249 15140424455100 --pell 109
+check 379516400906811930638014896080 12055735790331359447442538767 --pell 991
+check 48842 5967 --pell 67
+# Archimedes' cattle problem
+check 109931986732829734979866232821433543901088049 50549485234315033074477819735540408986340 --pell 4729494
+
+# The tuple columns match the --raw tuples of √D
+for d in 2 7 13 61; do
+    pell=$($EGYPT --pell $d 2>/dev/null | awk -F '\t' 'NR > 1 && NF == 7 { print $4 "\t" $5 "\t" $6 "\t" $7 }')
+    n=$(echo "$pell" | wc -l)
+    raw=$($EGYPT "$d sqrt" 1 --raw --tuples $n 2>/dev/null | sort)
+    if [ "$(echo "$pell" | sort)" == "$raw" ]; then
+        echo "ok   --pell $d: $n tuples match --raw"
+    else
+        echo "FAIL --pell $d: tuples differ from --raw"
+        failed=1
+    fi
+done
+
+# A perfect square has no solution
+if $EGYPT --pell 16 >/dev/null 2>&1; then
+    echo "FAIL --pell 16: expected exit code 1"
+    failed=1
+else
+    echo "ok   --pell 16: no solution"
+fi
+
+exit $failed
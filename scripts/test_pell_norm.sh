@@ -0,0 +1,77 @@
+#!/bin/bash
+# Generalised Pell test: --pell D --norm N --max-q B against a brute force search for
+# p² - D·q² = N over |q| ≤ B (p > 0, or q > 0 when p = 0), and the class count of known cases
+#
+# Usage: ./scripts/test_pell_norm.sh
+#   EGYPT - path to binary (default: ./target/release/egypt)
+
+EGYPT=${EGYPT:-./target/release/egypt}
+
+if [ ! -x "$EGYPT" ]; then
+    echo "Error: $EGYPT not found. Run 'cargo build --release' first."
+    exit 1
+fi
+
+failed=0
+
+# check <D> <N> <B>
+check() {
+    local got=$($EGYPT --pell "$1" --norm "$2" --max-q "$3" 2>/dev/null | tail -n +2 | cut -f 2,3 | sort)
+    local expected=$(awk -v d="$1" -v n="$2" -v b="$3" 'BEGIN {
+        for (q = -b; q <= b; q++) {
+            s = n + d * q * q
+            if (s < 0) continue
+            p = int(sqrt(s) + 0.5)
+            if (p * p == s && (p > 0 || q > 0)) printf "%d\t%d\n", q, p
+        }
+    }' | sort)
+    if [ "$got" == "$expected" ]; then
+        echo "ok   --pell $1 --norm $2 --max-q $3: $(echo "$got" | grep -c .) solutions"
+    else
+        echo "FAIL --pell $1 --norm $2 --max-q $3:"
+        diff <(echo "$expected") <(echo "$got")
+        failed=1
+    fi
+}
+
+# check_classes <D> <N> <classes>
+check_classes() {
+    local got=$($EGYPT --pell "$1" --norm "$2" 2>&1 >/dev/null | grep -o '[0-9]* class(es)')
+    if [ "$got" == "$3 class(es)" ]; then
+        echo "ok   --pell $1 --norm $2: $3 class(es)"
+    else
+        echo "FAIL --pell $1 --norm $2: got '$got', expected $3 class(es)"
+        failed=1
+    fi
+}
+
+for d in 2 3 5 6 7 10 13 19; do
+    for n in -12 -9 -8 -7 -4 -2 -1 1 2 3 4 5 9 12 18 25 36; do
+        check $d $n 3000
+    done
+done
+
+check_classes 13 -4 3
+check_classes 7 9 3
+check_classes 2 -7 2
+check_classes 13 1 1
+check_classes 6 5 0
+check_classes 61 -3 2
+
+# No solutions: exit code 1; N = 0: exit code 2
+$EGYPT --pell 6 --norm 5 >/dev/null 2>&1
+if [ $? -eq 1 ]; then
+    echo "ok   --pell 6 --norm 5: no solution"
+else
+    echo "FAIL --pell 6 --norm 5: expected exit code 1"
+    failed=1
+fi
+$EGYPT --pell 6 --norm 0 >/dev/null 2>&1
+if [ $? -eq 2 ]; then
+    echo "ok   --pell 6 --norm 0: rejected"
+else
+    echo "FAIL --pell 6 --norm 0: expected exit code 2"
+    failed=1
+fi
+
+exit $failed
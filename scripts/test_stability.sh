@@ -0,0 +1,45 @@
+#!/bin/bash
+# Stability test: tuples marked certified by --stability at a low precision must be the plain --raw
+# output and reappear at a high precision; --stable-only must print exactly the certified ones
+#
+# Usage: ./scripts/test_stability.sh [low bits] [high bits]
+#   EGYPT - path to binary (default: ./target/release/egypt)
+
+EGYPT=${EGYPT:-./target/release/egypt}
+LOW=${1:-64}
+HIGH=${2:-1024}
+
+if [ ! -x "$EGYPT" ]; then
+    echo "Error: $EGYPT not found. Run 'cargo build --release' first."
+    exit 1
+fi
+
+failed=0
+
+# check <numerator> <denominator>
+check() {
+    local marked=$($EGYPT "$1" "$2" --raw --stability -p "$LOW" 2>/dev/null)
+    local certified=$(echo "$marked" | awk -F'\t' '$5 == "certified" { print $1 "\t" $2 "\t" $3 "\t" $4 }')
+    local n=$(echo "$certified" | grep -c .)
+    local plain=$($EGYPT "$1" "$2" --raw -p "$LOW" 2>/dev/null)
+    local stable=$($EGYPT "$1" "$2" --raw --stability --stable-only -p "$LOW" 2>/dev/null | cut -f 1-4)
+    local high=$($EGYPT "$1" "$2" --raw -p "$HIGH" 2>/dev/null | head -n "$n")
+    local uncertain=$(echo "$marked" | grep -c uncertain)
+    if [ "$n" -gt 0 ] && [ "$certified" == "$plain" ] && [ "$stable" == "$plain" ] && [ "$certified" == "$high" ]; then
+        echo "ok   $1/$2: $n certified, $uncertain uncertain at $LOW bits"
+    else
+        echo "FAIL $1/$2: certified tuples at $LOW bits differ from --raw, --stable-only or $HIGH bits"
+        failed=1
+    fi
+}
+
+check pi 4
+check pi 1
+check e 3
+check gamma 1
+check ln2 1
+check "2 cbrt" 1
+check zeta3 catalan
+check "2 log 1 +" pi
+
+exit $failed
@@ -0,0 +1,82 @@
+#!/bin/bash
+# Stack test: stack words, bracketed aggregates, variables and --defs words must give known values,
+# and input using them must be auto-detected as RPN (infix calls as infix)
+#
+# Usage: ./scripts/test_stack.sh
+#   EGYPT - path to binary (default: ./target/release/egypt)
+
+EGYPT=${EGYPT:-./target/release/egypt}
+
+if [ ! -x "$EGYPT" ]; then
+    echo "Error: $EGYPT not found. Run 'cargo build --release' first."
+    exit 1
+fi
+
+failed=0
+defs=$(mktemp)
+trap 'rm -f "$defs"' EXIT
+cat > "$defs" <<'EOF'
+: sq dup * ;
+: hyp sq swap sq + sqrt ;   # 3 4 hyp = 5
+EOF
+
+# check <expression> <expected p/q> [args...]: the exact value is the last convergent of the input
+check() {
+    local got=$($EGYPT "$1" 1 --convergents "${@:3}" 2>/dev/null | tail -1 | awk -F'\t' '$5 == "0" { print $3 "/" $4 }')
+    if [ "$got" == "$2" ]; then
+        echo "ok   $1 = $2"
+    else
+        echo "FAIL $1: got '$got', expected $2"
+        failed=1
+    fi
+}
+
+# check_mode <rpn|infix> <expression> [args...]: auto-detection agrees with the forced mode,
+# for expressions the other mode reads differently or rejects
+check_mode() {
+    local auto=$($EGYPT "$2" 1 --cf "${@:3}" 2>&1)
+    local forced=$($EGYPT "$2" 1 --cf "--$1" "${@:3}" 2>&1)
+    local other=$([ "$1" == rpn ] && echo infix || echo rpn)
+    local wrong=$($EGYPT "$2" 1 --cf "--$other" "${@:3}" 2>&1)
+    if [ "$auto" == "$forced" ] && [ "$auto" != "$wrong" ]; then
+        echo "ok   '$2' is $1"
+    else
+        echo "FAIL '$2' not detected as $1"
+        failed=1
+    fi
+}
+
+check "2 dup *" "4/1"
+check "2 3 swap -" "1/1"
+check "1 2 over + +" "4/1"
+check "5 3 drop 1 +" "6/1"
+
+# Brackets fold only their own operands; without them the fold takes the whole stack
+check "10 [ 2 3 ] prod +" "16/1"
+check "10 2 3 prod" "60/1"
+check "[ 1 2 3 ] sum" "6/1"
+check "1 2 3 sum" "6/1"
+check "100 fib [ 1 10 seq ] sum /" "6440451785077489365/1"
+check "10 + prod(2, 3)" "16/1"
+
+# Variables can be read any number of times
+check "3 x = x x * x +" "12/1"
+check "100 fib f = f f 1 + /" "354224848179261915075/354224848179261915076"
+
+check "3 4 hyp" "5/1" --defs "$defs"
+check "3 sq 1 +" "10/1" --defs "$defs"
+check "hyp(3, 4) + 1" "6/1" --defs "$defs"
+
+# is_infix counts the stack depth through these words, so none of them looks like a stack underflow
+check_mode rpn "2 dup * 1 -"
+check_mode rpn "3 dup dup * *"
+check_mode rpn "2 3 swap - 5 *"
+check_mode rpn "7 2 3 swap - -"
+check_mode rpn "1 2 over - -"
+check_mode rpn "[ 1 2 ] sum 3 *"
+check_mode rpn "3 x = x x *"
+check_mode rpn "3 sq 2 sq - 1 +" --defs "$defs"
+check_mode infix "sum(1, 2) * 3"
+check_mode infix "sq(3) - 1" --defs "$defs"
+
+exit $failed
@@ -0,0 +1,88 @@
+#!/bin/bash
+# Unity test: --unity must print the known Sylvester, Znám and perfect-number representations of 1,
+# whose reciprocals sum to 1, and reject parameters outside each family with exit code 2
+#
+# Usage: ./scripts/test_unity.sh
+#   EGYPT - path to binary (default: ./target/release/egypt)
+
+EGYPT=${EGYPT:-./target/release/egypt}
+
+if [ ! -x "$EGYPT" ]; then
+    echo "Error: $EGYPT not found. Run 'cargo build --release' first."
+    exit 1
+fi
+
+failed=0
+
+# check <family> <parameter> <expected output, lines separated by ';'>
+check() {
+    local got=$($EGYPT --unity "$1" "$2" 2>/dev/null | tr '\t\n' ' ;')
+    if [ "$got" == "$3;" ]; then
+        echo "ok   --unity $1 $2: $3"
+    else
+        echo "FAIL --unity $1 $2: got '$got', expected '$3'"
+        failed=1
+    fi
+}
+
+# check_sums <family> <parameter>: the reciprocals of every printed line sum to exactly 1
+check_sums() {
+    local lines=0 bad=0
+    while read -r dens; do
+        lines=$((lines + 1))
+        local terms=$(for d in $dens; do printf '%s inv ' "$d"; done)
+        local sum=$($EGYPT --rpn "[ $terms] sum" 1 --convergents 2>/dev/null | tail -1 | cut -f 3,4)
+        [ "$sum" == "1	1" ] || bad=1
+    done < <($EGYPT --unity "$1" "$2" 2>/dev/null | cut -f 1)
+    if [ $lines -gt 0 ] && [ $bad -eq 0 ]; then
+        echo "ok   --unity $1 $2: $lines line(s) sum to 1"
+    else
+        echo "FAIL --unity $1 $2: a line does not sum to 1"
+        failed=1
+    fi
+}
+
+# check_error <expected message> <args...>
+check_error() {
+    local got
+    got=$($EGYPT "${@:2}" 2>&1 >/dev/null)
+    local code=$?
+    if [ $code -eq 2 ] && [[ "$got" == *"$1"* ]]; then
+        echo "ok   ${*:2}: $1"
+    else
+        echo "FAIL ${*:2}: exit code $code, got '$got', expected '$1'"
+        failed=1
+    fi
+}
+
+check sylvester 1 "2 2"
+check sylvester 3 "2 3 7 42"
+check sylvester 4 "2 3 7 43 1806"
+# {2, 3, 11, 23, 31} is the smallest proper Znám set of size 5
+check znam 5 "2 3 7 43 1807 3263442 improper;2 3 7 47 395 779730 proper;2 3 11 23 31 47058 proper"
+check znam 2 "2 3 6 improper"
+check perfect 28 "2 4 7 14 28"
+check perfect 496 "2 4 8 16 31 62 124 248 496"
+check perfect 12 "2 3 4 6 12 sum 4/3"
+
+check_sums sylvester 10
+check_sums znam 6
+check_sums perfect "2 31 ^ 1 - 2 30 ^ *"
+
+check_error "must be positive and below 2^128" --unity perfect 0
+check_error "must be positive and below 2^128" --unity perfect "2 130 ^"
+check_error "out of range" --unity sylvester -- -1
+check_error "out of range" --unity znam -- -2
+check_error "must be at least 2" --unity znam 1
+
+# --unity verifies with unit fractions even when the output is --raw
+for family in "sylvester 4" "znam 5" "perfect 496"; do
+    if $EGYPT --unity $family --raw >/dev/null 2>&1; then
+        echo "ok   --unity $family --raw: verified"
+    else
+        echo "FAIL --unity $family --raw: verification failed"
+        failed=1
+    fi
+done
+
+exit $failed
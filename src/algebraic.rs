@@ -0,0 +1,279 @@
+//! Real algebraic numbers given by an integer polynomial and a root index
+//! Roots are isolated exactly (Descartes' rule of signs with bisection, Akritas–Collins) and
+//! expanded into CFs with Vincent's transformations x → a + 1/x, without floating point.
+
+use rug::{Integer, Rational};
+
+/// Polynomial coefficients, lowest degree first
+type Poly = Vec<Rational>;
+
+/// Parse coefficients given highest degree first, separated by spaces or commas: "1 0 -1 -1" = x³ - x - 1
+pub fn parse_polynomial(s: &str) -> Result<Vec<Integer>, String> {
+    let mut coeffs = s.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|t| !t.is_empty())
+        .map(|t| t.parse::<Integer>().map_err(|_| format!("coefficient '{}' is not an integer", t)))
+        .collect::<Result<Vec<_>, _>>()?;
+    coeffs.reverse();
+    while coeffs.last().is_some_and(|c| c.is_zero()) {
+        coeffs.pop();
+    }
+    if coeffs.len() < 2 {
+        return Err("polynomial must have degree 1 or more".to_string());
+    }
+    Ok(coeffs)
+}
+
+/// Human readable polynomial, e.g. x^3 - x - 1
+pub fn format_polynomial(p: &[Integer]) -> String {
+    let mut ret = String::new();
+    for (k, c) in p.iter().enumerate().rev().filter(|(_, c)| !c.is_zero()) {
+        let sign = if *c < 0 { "-" } else { "+" };
+        if ret.is_empty() {
+            ret.push_str(if *c < 0 { "-" } else { "" });
+        } else {
+            ret.push_str(&format!(" {} ", sign));
+        }
+        let magnitude = c.clone().abs();
+        if magnitude != 1 || k == 0 {
+            ret.push_str(&magnitude.to_string());
+        }
+        match k {
+            0 => {}
+            1 => ret.push('x'),
+            _ => ret.push_str(&format!("x^{}", k)),
+        }
+    }
+    ret
+}
+
+fn eval(p: &[Rational], x: &Rational) -> Rational {
+    p.iter().rev().fold(Rational::new(), |acc, c| acc * x + c)
+}
+
+fn sign(p: &[Rational], x: &Rational) -> std::cmp::Ordering {
+    eval(p, x).cmp0()
+}
+
+fn derivative(p: &[Rational]) -> Poly {
+    p.iter().enumerate().skip(1).map(|(k, c)| Rational::from(c * k as u32)).collect()
+}
+
+fn trim(mut p: Poly) -> Poly {
+    while p.last().is_some_and(|c| c.is_zero()) {
+        p.pop();
+    }
+    p
+}
+
+/// Quotient and remainder of polynomial division over Q
+fn div_rem(a: &[Rational], b: &[Rational]) -> (Poly, Poly) {
+    let mut rem = a.to_vec();
+    let lead = b.last().unwrap();
+    let mut quot = vec![Rational::new(); a.len().saturating_sub(b.len()) + 1];
+    while rem.len() >= b.len() && !rem.is_empty() {
+        let shift = rem.len() - b.len();
+        let factor = Rational::from(rem.last().unwrap() / lead);
+        for (k, c) in b.iter().enumerate() {
+            rem[shift + k] -= Rational::from(&factor * c);
+        }
+        quot[shift] = factor;
+        rem.pop();
+        rem = trim(rem);
+    }
+    (quot, rem)
+}
+
+fn gcd(a: &[Rational], b: &[Rational]) -> Poly {
+    let (mut a, mut b) = (a.to_vec(), b.to_vec());
+    while !b.is_empty() {
+        let r = div_rem(&a, &b).1;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+/// p(x + c)
+fn shift(p: &[Rational], c: &Rational) -> Poly {
+    let mut ret = p.to_vec();
+    let n = ret.len();
+    for i in 0..n {
+        for k in (i..n - 1).rev() {
+            let t = Rational::from(&ret[k + 1] * c);
+            ret[k] += t;
+        }
+    }
+    ret
+}
+
+/// p(s·x)
+fn scale(p: &[Rational], s: &Rational) -> Poly {
+    let mut power = Rational::from(1);
+    p.iter().map(|c| {
+        let ret = Rational::from(c * &power);
+        power *= s;
+        ret
+    }).collect()
+}
+
+/// xⁿ·p(1/x)
+fn reverse(p: &[Rational]) -> Poly {
+    p.iter().rev().cloned().collect()
+}
+
+/// Sign variations of the coefficient sequence, ignoring zeros
+fn variations(p: &[Rational]) -> usize {
+    let signs = p.iter().filter(|c| !c.is_zero()).map(|c| *c > 0).collect::<Vec<_>>();
+    signs.windows(2).filter(|w| w[0] != w[1]).count()
+}
+
+/// Descartes' bound on the roots in the open interval (l, r): roots of p(l + (r - l)x) in (0, 1)
+/// mapped to (0, ∞) by x → 1/(x + 1); exact when 0 or 1
+fn descartes(p: &[Rational], l: &Rational, r: &Rational) -> usize {
+    let unit = scale(&shift(p, l), &Rational::from(r - l));
+    variations(&shift(&reverse(&unit), &Rational::from(1)))
+}
+
+/// A real root: exactly rational, or alone in an open interval
+#[derive(Debug, Clone)]
+pub enum Root {
+    Rational(Rational),
+    Isolated(Rational, Rational),
+}
+
+/// Square-free part p / gcd(p, p'), which has the same roots, all simple
+fn square_free(p: &[Integer]) -> Poly {
+    let p = p.iter().map(Rational::from).collect::<Vec<_>>();
+    let g = gcd(&p, &derivative(&p));
+    div_rem(&p, &g).0
+}
+
+/// All real roots in increasing order
+pub fn real_roots(p: &[Integer]) -> Vec<Root> {
+    let q = square_free(p);
+    // Cauchy bound: every root has |x| < 1 + max |a_k / a_n|
+    let lead = q.last().unwrap().clone().abs();
+    let bound: Rational = q.iter().map(|c| Rational::from(c.abs_ref()) / &lead).max().unwrap() + 1;
+    let mut roots = vec![];
+    let mut pending = vec![(-bound.clone(), bound)];
+    while let Some((l, r)) = pending.pop() {
+        match descartes(&q, &l, &r) {
+            0 => {}
+            1 => roots.push(Root::Isolated(l, r)),
+            _ => {
+                let m = Rational::from(&l + &r) / 2;
+                if eval(&q, &m).is_zero() {
+                    roots.push(Root::Rational(m.clone()));
+                }
+                pending.push((l, m.clone()));
+                pending.push((m, r));
+            }
+        }
+    }
+    roots.sort_by(|a, b| left(a).cmp(left(b)));
+    roots
+}
+
+fn left(root: &Root) -> &Rational {
+    match root {
+        Root::Rational(x) | Root::Isolated(x, _) => x,
+    }
+}
+
+/// Lazy CF of the absolute value of an isolated root
+/// Invariant: the polynomial has exactly one root β in (lo, hi), simple and positive, and β is
+/// the complete quotient still to expand; hi = None stands for ∞
+pub struct RootCf {
+    poly: Poly,
+    lo: Rational,
+    hi: Option<Rational>,
+    done: bool,
+}
+
+impl RootCf {
+    /// CF of |α| for the root α of p isolated in (l, r), or the exact value if α is rational
+    pub fn new(p: &[Integer], l: &Rational, r: &Rational) -> Result<RootCf, Rational> {
+        let q = square_free(p);
+        // bisect until the interval lies on one side of 0 and its ends are not roots
+        // (bisection can leave a rational root as the end of a neighbouring interval)
+        let (mut l, mut r) = (l.clone(), r.clone());
+        while (l < 0 && r > 0) || eval(&q, &l).is_zero() || eval(&q, &r).is_zero() {
+            let m = if l < 0 && r > 0 { Rational::new() } else { Rational::from(&l + &r) / 2 };
+            if eval(&q, &m).is_zero() {
+                return Err(m);
+            }
+            if descartes(&q, &l, &m) == 1 {
+                r = m;
+            } else {
+                l = m;
+            }
+        }
+        Ok(if r <= 0 {
+            // |α| is the root of p(-x) in (-r, -l)
+            RootCf { poly: scale(&q, &Rational::from(-1)), lo: -r, hi: Some(-l), done: false }
+        } else {
+            RootCf { poly: q, lo: l, hi: Some(r), done: false }
+        })
+    }
+
+    /// Largest integer at most β, or Err(β) when β is that integer
+    fn floor(&mut self) -> Result<Integer, Integer> {
+        let at_infinity = self.poly.last().unwrap().cmp0();
+        if self.hi.is_none() {
+            // β is the only root above lo, so the sign of ∞ holds from β on
+            let mut m = self.lo.clone().ceil().max(Rational::from(1)) * 2;
+            loop {
+                match sign(&self.poly, &m) {
+                    std::cmp::Ordering::Equal => return Err(m.into_numer_denom().0),
+                    s if s == at_infinity => break,
+                    _ => {
+                        self.lo = m.clone();
+                        m *= 2;
+                    }
+                }
+            }
+            self.hi = Some(m);
+        }
+        let hi_sign = sign(&self.poly, self.hi.as_ref().unwrap());
+        loop {
+            let hi = self.hi.as_ref().unwrap();
+            let next: Rational = self.lo.clone().floor() + 1;
+            if next >= *hi {
+                return Ok(self.lo.clone().floor().into_numer_denom().0);
+            }
+            // bisect at an integer inside (lo, hi)
+            let mid: Rational = Rational::from(&self.lo + hi) / 2;
+            let m = mid.floor().max(next);
+            match sign(&self.poly, &m) {
+                std::cmp::Ordering::Equal => return Err(m.into_numer_denom().0),
+                s if s == hi_sign => self.hi = Some(m),
+                _ => self.lo = m,
+            }
+        }
+    }
+}
+
+impl Iterator for RootCf {
+    type Item = Integer;
+
+    fn next(&mut self) -> Option<Integer> {
+        if self.done {
+            return None;
+        }
+        let a = match self.floor() {
+            Ok(a) => a,
+            Err(a) => {
+                self.done = true;
+                return Some(a);
+            }
+        };
+        // β = a + 1/β': the root of xⁿ·p(a + 1/x) in (1/(hi - a), 1/(lo - a))
+        let ra = Rational::from(&a);
+        self.poly = reverse(&shift(&self.poly, &ra));
+        let hi = self.hi.take().unwrap();
+        let lo = std::mem::take(&mut self.lo);
+        self.lo = Rational::from(1) / (hi - &ra);
+        self.hi = if lo == ra { None } else { Some(Rational::from(1) / (lo - &ra)) };
+        Some(a)
+    }
+}
@@ -0,0 +1,99 @@
+//! Best rational approximation p/q with q ≤ Q, optionally with q coprime to M (--best-approx, --coprime)
+
+use rug::{Integer, Rational};
+
+use crate::convergents::approximations;
+
+/// Neighbours lo < x < hi among the allowed fractions, and the one closest to x (x itself if allowed)
+pub struct Best {
+    pub lo: Rational,
+    pub hi: Rational,
+    pub best: Rational,
+}
+
+/// Neighbours of a/b in the Farey sequence F_Q via modular inverses:
+/// the left one c/d has a·d - b·c = 1, so d ≡ a⁻¹ (mod b); the right one has d ≡ -a⁻¹ (mod b);
+/// each takes the largest such d ≤ Q
+fn farey_neighbours(x: &Rational, q_max: &Integer) -> (Rational, Rational) {
+    let (a, b) = (x.numer(), x.denom());
+    let inv = Integer::from(a % b).invert(b).unwrap_or_default();
+    let largest = |d0: Integer| Integer::from(q_max - &d0) / b * b + d0;
+    let d = largest(inv.clone());
+    let lo = Rational::from(((Integer::from(a * &d) - 1) / b, d));
+    let d = largest(Integer::from(b - &inv) % b);
+    let hi = Rational::from(((Integer::from(a * &d) + 1) / b, d));
+    (lo, hi)
+}
+
+/// Term of F_Q before a, where a < b are consecutive
+fn before(a: &Rational, b: &Rational, q_max: &Integer) -> Rational {
+    let k = Integer::from(q_max + b.denom()) / a.denom();
+    Rational::from((Integer::from(&k * a.numer()) - b.numer(), Integer::from(&k * a.denom()) - b.denom()))
+}
+
+/// Term of F_Q after b, where a < b are consecutive
+fn after(a: &Rational, b: &Rational, q_max: &Integer) -> Rational {
+    let k = Integer::from(q_max + a.denom()) / b.denom();
+    Rational::from((Integer::from(&k * b.numer()) - a.numer(), Integer::from(&k * b.denom()) - a.denom()))
+}
+
+fn allowed(x: &Rational, modulus: Option<&Integer>) -> bool {
+    modulus.is_none_or(|m| Integer::from(x.denom().gcd_ref(m)) == 1)
+}
+
+/// Best approximation of x = [a_0; a_1, ...] with denominator at most Q (and coprime to M)
+/// complete: the quotients are the whole CF of a rational, otherwise a certified prefix of an irrational;
+/// None when the prefix is too short to decide (its last convergent denominator must exceed Q)
+pub fn best_approximation(cf: &[Integer], complete: bool, q_max: &Integer, modulus: Option<&Integer>) -> Option<Best> {
+    let cs = approximations(cf, complete, false);
+    let last = cs.last()?;
+    let x = complete.then(|| Rational::from((last.p.clone(), last.q.clone())));
+    let (lo, hi) = if complete && last.q <= *q_max {
+        farey_neighbours(x.as_ref().unwrap(), q_max)
+    } else {
+        if last.q <= *q_max {
+            return None;
+        }
+        // the last convergent p_k/q_k within Q and the semiconvergent
+        // (p_{k-1} + m·p_k)/(q_{k-1} + m·q_k) with the largest m keeping q ≤ Q are the neighbours of x in F_Q
+        let k = cs.iter().rposition(|c| c.q <= *q_max).unwrap();
+        let (p1, q1) = if k == 0 { (Integer::from(1), Integer::new()) } else { (cs[k - 1].p.clone(), cs[k - 1].q.clone()) };
+        let m = Integer::from(q_max - &q1) / &cs[k].q;
+        let c = Rational::from((cs[k].p.clone(), cs[k].q.clone()));
+        let s = Rational::from((Integer::from(&m * &cs[k].p) + p1, Integer::from(&m * &cs[k].q) + q1));
+        if c < s { (c, s) } else { (s, c) }
+    };
+    // walk outwards through F_Q to the nearest allowed denominators
+    let (mut lo_next, mut lo) = (x.clone().filter(|x| x.denom() <= q_max).unwrap_or(hi.clone()), lo);
+    let (mut hi_prev, mut hi) = (x.clone().filter(|x| x.denom() <= q_max).unwrap_or(lo.clone()), hi);
+    while !allowed(&lo, modulus) {
+        let prev = before(&lo, &lo_next, q_max);
+        lo_next = std::mem::replace(&mut lo, prev);
+    }
+    while !allowed(&hi, modulus) {
+        let next = after(&hi_prev, &hi, q_max);
+        hi_prev = std::mem::replace(&mut hi, next);
+    }
+    let mid = Rational::from(&lo + &hi) / 2;
+    let closer_to_lo = match &x {
+        Some(x) if x.denom() <= q_max && allowed(x, modulus) => return Some(Best { lo, hi, best: x.clone() }),
+        // a tie goes to the smaller denominator
+        Some(x) => *x < mid || (*x == mid && lo.denom() <= hi.denom()),
+        None => {
+            // x lies strictly between p_K/q_K and (p_K + p_{K-1})/(q_K + q_{K-1})
+            let (p, q) = (&last.p, &last.q);
+            let (p1, q1) = if cs.len() > 1 { (cs[cs.len() - 2].p.clone(), cs[cs.len() - 2].q.clone()) } else { (Integer::from(1), Integer::new()) };
+            let c = Rational::from((p.clone(), q.clone()));
+            let mediant = Rational::from((p1 + p, q1 + q));
+            if c <= mid && mediant <= mid {
+                true
+            } else if c >= mid && mediant >= mid {
+                false
+            } else {
+                return None;
+            }
+        }
+    };
+    let best = if closer_to_lo { lo.clone() } else { hi.clone() };
+    Some(Best { lo, hi, best })
+}
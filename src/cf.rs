@@ -0,0 +1,398 @@
+//! Lazy continued fractions and Gosper's bihomographic arithmetic on them
+//! Quotients are regular: a_0 = ⌊x⌋, then a_k ≥ 1. Combined streams only emit a quotient once
+//! every value the inputs may still take agrees on it, so the output is exact as far as it goes.
+
+use std::collections::VecDeque;
+use rug::{Integer, Rational};
+
+use crate::interval::Interval;
+use crate::quadratic::{PeriodicCf, Quad};
+
+/// Inputs read by a bihomographic stream without emitting a quotient before it gives up;
+/// an irrational combination with a rational value (√2 + 1 - √2) never settles otherwise
+const MAX_INGEST: usize = 1_000;
+
+/// Next element of a CF stream
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    Quotient(Integer),
+    /// The CF is finite and complete: the value is rational
+    End,
+    /// No further quotient can be certified
+    Unknown,
+}
+
+/// A stream of regular CF quotients
+pub trait CfSource {
+    fn next_term(&mut self) -> Term;
+}
+
+/// Known quotients, then End for an exact rational or Unknown for a certified prefix
+pub struct Finite {
+    terms: VecDeque<Integer>,
+    exact: bool,
+}
+
+impl Finite {
+    pub fn rational(r: &Rational) -> Finite {
+        Finite { terms: floor_cf(r).into(), exact: true }
+    }
+
+    /// Quotients shared by every point of the interval: the common prefix of the CFs of both
+    /// endpoints, without the last quotient of either
+    pub fn certified(x: &Interval) -> Finite {
+        let (lo, hi) = x.to_rationals();
+        let (cf_lo, cf_hi) = (floor_cf(&lo), floor_cf(&hi));
+        let n = cf_lo.len().min(cf_hi.len()).saturating_sub(1);
+        let terms = cf_lo.into_iter().zip(cf_hi).take(n).take_while(|(a, b)| a == b).map(|(a, _)| a).collect();
+        Finite { terms, exact: false }
+    }
+}
+
+impl CfSource for Finite {
+    fn next_term(&mut self) -> Term {
+        match self.terms.pop_front() {
+            Some(a) => Term::Quotient(a),
+            None if self.exact => Term::End,
+            None => Term::Unknown,
+        }
+    }
+}
+
+/// Regular CF of a rational with floor division, so negative values start with a_0 < 0
+fn floor_cf(r: &Rational) -> Vec<Integer> {
+    let (mut a, mut b) = r.clone().into_numer_denom();
+    let mut quotients = vec![];
+    while !b.is_zero() {
+        let (q, rem) = a.div_rem_floor(b.clone());
+        quotients.push(q);
+        a = b;
+        b = rem;
+    }
+    quotients
+}
+
+/// Eventually periodic CF of a quadratic irrational, without end
+pub struct Periodic {
+    cf: PeriodicCf,
+    index: usize,
+}
+
+impl Periodic {
+    pub fn new(cf: PeriodicCf) -> Periodic {
+        Periodic { cf, index: 0 }
+    }
+}
+
+impl CfSource for Periodic {
+    fn next_term(&mut self) -> Term {
+        let (pre, period) = (&self.cf.pre, &self.cf.period);
+        let a = if self.index < pre.len() {
+            pre[self.index].clone()
+        } else {
+            period[(self.index - pre.len()) % period.len()].clone()
+        };
+        self.index += 1;
+        Term::Quotient(a)
+    }
+}
+
+/// Constants with a known CF pattern, for a positive integer n
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    /// e^(1/n) = [1; n - 1, 1, 1, 3n - 1, 1, 1, 5n - 1, ...]; e = [2; 1, 2, 1, 1, 4, 1, 1, 6, ...]
+    Exp(Integer),
+    /// tanh(1/n) = [0; n, 3n, 5n, ...]
+    Tanh(Integer),
+    /// tan(1/n) = [0; n - 1, 1, 3n - 2, 1, 5n - 2, ...]; tan(1) = [1; 1, 1, 3, 1, 5, ...]
+    Tan(Integer),
+}
+
+impl Pattern {
+    /// Quotient a_k
+    fn quotient(&self, k: u64) -> Integer {
+        match self {
+            Pattern::Exp(n) if *n == 1 => match k {
+                0 => Integer::from(2),
+                _ if k % 3 == 2 => Integer::from(2 * (k + 1) / 3),
+                _ => Integer::from(1),
+            },
+            Pattern::Exp(n) => match k {
+                0 => Integer::from(1),
+                _ if k % 3 == 1 => Integer::from(2 * (k / 3) + 1) * n - 1,
+                _ => Integer::from(1),
+            },
+            Pattern::Tanh(n) => match k {
+                0 => Integer::new(),
+                _ => Integer::from(2 * k - 1) * n,
+            },
+            Pattern::Tan(n) if *n == 1 => match k {
+                0 => Integer::from(1),
+                _ if k % 2 == 1 => Integer::from(k),
+                _ => Integer::from(1),
+            },
+            Pattern::Tan(n) => match k {
+                0 => Integer::new(),
+                1 => Integer::from(n - 1),
+                _ if k % 2 == 1 => Integer::from(k) * n - 2,
+                _ => Integer::from(1),
+            },
+        }
+    }
+
+    pub fn to_interval(&self, precision: u32) -> Interval {
+        let inv = |n: &Integer| Interval::exact(&Rational::from((Integer::from(1), n.clone())), precision);
+        match self {
+            Pattern::Exp(n) => inv(n).exp(),
+            Pattern::Tanh(n) => inv(n).tanh(),
+            Pattern::Tan(n) => inv(n).tan().unwrap(),
+        }
+    }
+}
+
+impl std::fmt::Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::Exp(n) if *n == 1 => write!(f, "e"),
+            Pattern::Exp(n) => write!(f, "e^(1/{})", n),
+            Pattern::Tanh(n) => write!(f, "tanh(1/{})", n),
+            Pattern::Tan(n) => write!(f, "tan(1/{})", n),
+        }
+    }
+}
+
+/// Endless stream of a CF pattern
+pub struct Generated {
+    pattern: Pattern,
+    k: u64,
+}
+
+impl CfSource for Generated {
+    fn next_term(&mut self) -> Term {
+        self.k += 1;
+        Term::Quotient(self.pattern.quotient(self.k - 1))
+    }
+}
+
+/// Exact arithmetic on values without a common field, e.g. √2 + √3, expanded through CF streams
+#[derive(Debug, Clone)]
+pub enum CfExpr {
+    Rational(Rational),
+    Quad(Quad),
+    Pattern(Pattern),
+    Binary(char, Box<CfExpr>, Box<CfExpr>),
+}
+
+impl CfExpr {
+    pub fn source(&self) -> Box<dyn CfSource> {
+        match self {
+            CfExpr::Rational(r) => Box::new(Finite::rational(r)),
+            CfExpr::Quad(q) => Box::new(Periodic::new(q.periodic_cf())),
+            CfExpr::Pattern(p) => Box::new(Generated { pattern: p.clone(), k: 0 }),
+            CfExpr::Binary(op, x, y) => Box::new(match op {
+                '+' => Bihomographic::add(x.source(), y.source()),
+                '-' => Bihomographic::sub(x.source(), y.source()),
+                '*' => Bihomographic::mul(x.source(), y.source()),
+                _ => Bihomographic::div(x.source(), y.source()),
+            }),
+        }
+    }
+
+    /// Enclosure for interval arithmetic; unbounded if a divisor is not separated from 0
+    pub fn to_interval(&self, precision: u32) -> Interval {
+        match self {
+            CfExpr::Rational(r) => Interval::exact(r, precision),
+            CfExpr::Quad(q) => q.to_interval(precision),
+            CfExpr::Pattern(p) => p.to_interval(precision),
+            CfExpr::Binary(op, x, y) => {
+                let (x, y) = (x.to_interval(precision), y.to_interval(precision));
+                match op {
+                    '+' => x.add(&y),
+                    '-' => x.sub(&y),
+                    '*' => x.mul(&y),
+                    _ => x.div(&y).unwrap_or_else(|_| Interval::unbounded(precision)),
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for CfExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CfExpr::Rational(r) => write!(f, "{}", r),
+            CfExpr::Quad(q) => write!(f, "({})", q),
+            CfExpr::Pattern(p) => write!(f, "{}", p),
+            CfExpr::Binary(op, x, y) => write!(f, "({} {} {})", x, op, y),
+        }
+    }
+}
+
+/// z = (a·xy + b·x + c·y + d) / (e·xy + f·x + g·y + h) for CF streams x and y (Gosper)
+pub struct Bihomographic {
+    x: Box<dyn CfSource>,
+    y: Box<dyn CfSource>,
+    /// [a, b, c, d, e, f, g, h]
+    m: [Integer; 8],
+    /// Whether a_0 of x and y has been read; only the tails are known to lie in [1, ∞]
+    started: (bool, bool),
+    ended: (bool, bool),
+    /// Emit the CF of |z|: the first quotient settles the sign
+    absolute: bool,
+    emitted: bool,
+    state: Option<Term>,
+}
+
+impl Bihomographic {
+    pub fn new(x: Box<dyn CfSource>, y: Box<dyn CfSource>, m: [i32; 8]) -> Bihomographic {
+        Bihomographic {
+            x, y, m: m.map(Integer::from), started: (false, false), ended: (false, false),
+            absolute: false, emitted: false, state: None,
+        }
+    }
+
+    /// Stream |z| instead of z
+    pub fn abs(self) -> Bihomographic {
+        Bihomographic { absolute: true, ..self }
+    }
+
+    pub fn add(x: Box<dyn CfSource>, y: Box<dyn CfSource>) -> Bihomographic {
+        Bihomographic::new(x, y, [0, 1, 1, 0, 0, 0, 0, 1])
+    }
+
+    pub fn sub(x: Box<dyn CfSource>, y: Box<dyn CfSource>) -> Bihomographic {
+        Bihomographic::new(x, y, [0, 1, -1, 0, 0, 0, 0, 1])
+    }
+
+    pub fn mul(x: Box<dyn CfSource>, y: Box<dyn CfSource>) -> Bihomographic {
+        Bihomographic::new(x, y, [1, 0, 0, 0, 0, 0, 0, 1])
+    }
+
+    pub fn div(x: Box<dyn CfSource>, y: Box<dyn CfSource>) -> Bihomographic {
+        Bihomographic::new(x, y, [0, 1, 0, 0, 0, 0, 1, 0])
+    }
+
+    /// Corners (numerator, denominator) of z for CF tails x, y ∈ {∞, 1}, in the order
+    /// (∞, ∞), (∞, 1), (1, ∞), (1, 1), without the inputs already absorbed
+    fn corners(&self) -> Vec<(Integer, Integer)> {
+        let [a, b, c, d, e, f, g, h] = &self.m;
+        let all = [
+            (a.clone(), e.clone()),
+            (Integer::from(a + b), Integer::from(e + f)),
+            (Integer::from(a + c), Integer::from(e + g)),
+            (Integer::from(a + b) + c + d, Integer::from(e + f) + g + h),
+        ];
+        let [xy, x, y, one] = all;
+        match self.ended {
+            (false, false) => vec![xy, x, y, one],
+            (true, false) => vec![y, one],
+            (false, true) => vec![x, one],
+            (true, true) => vec![one],
+        }
+    }
+
+    /// ⌊z⌋ if every corner agrees and the denominator keeps one sign between them
+    /// (with x = 1 + X, y = 1 + Y the corner denominators are its coefficients in X, Y ≥ 0)
+    fn settled(&self) -> Option<Integer> {
+        if !self.started.0 || !self.started.1 {
+            return None;
+        }
+        let corners = self.corners();
+        let sign = corners[0].1.cmp0();
+        if sign == std::cmp::Ordering::Equal || corners.iter().any(|(_, d)| d.cmp0() != sign) {
+            return None;
+        }
+        let floors = corners.into_iter().map(|(n, d)| n.div_rem_floor(d).0).collect::<Vec<_>>();
+        floors.iter().all(|q| *q == floors[0]).then(|| floors[0].clone())
+    }
+
+    /// Read x rather than y: unread or unbounded x first, else the input moving z the most
+    fn read_x(&self) -> bool {
+        if self.ended.0 || (!self.started.1 && self.started.0) {
+            return false;
+        }
+        if self.ended.1 || !self.started.0 {
+            return true;
+        }
+        let [_, x, y, one] = <[_; 4]>::try_from(self.corners()).unwrap();
+        let ratio = |(n, d): (Integer, Integer)| (!d.is_zero()).then(|| Rational::from((n, d)));
+        match (ratio(x), ratio(y), ratio(one)) {
+            (Some(bx), Some(cy), Some(d)) => (bx - &d).abs() >= (cy - d).abs(),
+            (None, _, _) | (_, _, None) => true,
+            (_, None, _) => false,
+        }
+    }
+
+    /// x = p + 1/x' (None: x = ∞)
+    fn absorb_x(&mut self, p: Option<Integer>) {
+        let [a, b, c, d, e, f, g, h] = std::mem::take(&mut self.m);
+        let zero = Integer::new;
+        self.m = match p {
+            Some(p) => [Integer::from(&a * &p) + c, Integer::from(&b * &p) + d, a, b,
+                        Integer::from(&e * &p) + g, Integer::from(&f * &p) + h, e, f],
+            None => [zero(), zero(), a, b, zero(), zero(), e, f],
+        };
+    }
+
+    /// y = q + 1/y' (None: y = ∞)
+    fn absorb_y(&mut self, q: Option<Integer>) {
+        let [a, b, c, d, e, f, g, h] = std::mem::take(&mut self.m);
+        let zero = Integer::new;
+        self.m = match q {
+            Some(q) => [Integer::from(&a * &q) + b, a, Integer::from(&c * &q) + d, c,
+                        Integer::from(&e * &q) + f, e, Integer::from(&g * &q) + h, g],
+            None => [zero(), a, zero(), c, zero(), e, zero(), g],
+        };
+    }
+}
+
+impl CfSource for Bihomographic {
+    fn next_term(&mut self) -> Term {
+        if let Some(state) = &self.state {
+            return state.clone();
+        }
+        for _ in 0..MAX_INGEST {
+            if self.started == (true, true) && self.corners().iter().all(|(_, d)| d.is_zero()) {
+                self.state = Some(Term::End);
+                return Term::End;
+            }
+            if let Some(q) = self.settled() {
+                if self.absolute && !self.emitted && q < 0 {
+                    // z < 0: continue with -z
+                    for k in 0..4 {
+                        self.m[k] = -std::mem::take(&mut self.m[k]);
+                    }
+                    continue;
+                }
+                self.emitted = true;
+                // z = q + 1/z'
+                let [a, b, c, d, e, f, g, h] = std::mem::take(&mut self.m);
+                self.m = [e.clone(), f.clone(), g.clone(), h.clone(),
+                          a - Integer::from(&q * &e), b - Integer::from(&q * &f),
+                          c - Integer::from(&q * &g), d - Integer::from(&q * &h)];
+                return Term::Quotient(q);
+            }
+            let x = self.read_x();
+            let term = if x { self.x.next_term() } else { self.y.next_term() };
+            let p = match term {
+                Term::Quotient(p) => Some(p),
+                Term::End => None,
+                Term::Unknown => {
+                    self.state = Some(Term::Unknown);
+                    return Term::Unknown;
+                }
+            };
+            if x {
+                self.ended.0 = p.is_none();
+                self.started.0 = true;
+                self.absorb_x(p);
+            } else {
+                self.ended.1 = p.is_none();
+                self.started.1 = true;
+                self.absorb_y(p);
+            }
+        }
+        self.state = Some(Term::Unknown);
+        Term::Unknown
+    }
+}
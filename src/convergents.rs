@@ -0,0 +1,96 @@
+//! Partial quotients, convergents and semiconvergents of a CF (--cf, --convergents)
+
+use rug::Integer;
+
+use crate::{as_egyptian_fraction_symbolic_from_cf, Args};
+
+/// Convergent p_k/q_k (m = a_k) or semiconvergent (m·p_{k-1} + p_{k-2})/(m·q_{k-1} + q_{k-2}), 0 < m < a_k
+pub struct Approximation {
+    pub k: usize,
+    pub m: Integer,
+    pub p: Integer,
+    pub q: Integer,
+    /// N with |x - p/q| ≤ 1/N, None when p/q is x itself
+    pub bound: Option<Integer>,
+}
+
+/// Convergents of [a_0; a_1, ...] with error bounds, each preceded by its semiconvergents if asked
+/// complete: the quotients are the whole CF of a rational, otherwise a certified prefix of an irrational
+pub fn approximations(cf: &[Integer], complete: bool, semiconvergents: bool) -> Vec<Approximation> {
+    // p_{-2}/q_{-2} = 0/1, p_{-1}/q_{-1} = 1/0
+    let mut ps = vec![Integer::new(), Integer::from(1)];
+    let mut qs = vec![Integer::from(1), Integer::new()];
+    for a in cf {
+        let n = ps.len();
+        ps.push(Integer::from(a * &ps[n - 1]) + &ps[n - 2]);
+        qs.push(Integer::from(a * &qs[n - 1]) + &qs[n - 2]);
+    }
+    // index k + 2 holds p_k, q_k
+    let mut ret = vec![];
+    for (k, a) in cf.iter().enumerate() {
+        if semiconvergents && k > 0 {
+            let mut m = Integer::from(1);
+            while m < *a {
+                let p = Integer::from(&m * &ps[k + 1]) + &ps[k];
+                let q = Integer::from(&m * &qs[k + 1]) + &qs[k];
+                // p/q lies between p_{k-2}/q_{k-2} and p_k/q_k, on the side of x opposite to p_{k-1}/q_{k-1},
+                // so |x - p/q| < |p_{k-1}/q_{k-1} - p/q| = 1/(q_{k-1}·q)
+                let bound = Some(Integer::from(&qs[k + 1] * &q));
+                ret.push(Approximation { k, m: m.clone(), p, q, bound });
+                m += 1;
+            }
+        }
+        // |x - p_k/q_k| = 1/(q_k·(α_{k+1}·q_k + q_{k-1})) for the complete quotient α_{k+1} ≥ a_{k+1},
+        // and α_{k+1} > 1 when a_{k+1} is not certified yet
+        let bound = if k + 1 < cf.len() {
+            Some(Integer::from(&qs[k + 2] * &qs[k + 3]))
+        } else if complete {
+            None
+        } else {
+            Some(Integer::from(&qs[k + 2] + &qs[k + 1]) * &qs[k + 2])
+        };
+        ret.push(Approximation { k, m: a.clone(), p: ps[k + 2].clone(), q: qs[k + 2].clone(), bound });
+    }
+    ret
+}
+
+/// Print the quotients (--cf) or the convergent table (--convergents)
+/// --with-tuples appends the raw tuple that brings the Egyptian sum to each convergent:
+/// (a_0, 0, 0, 0) at k = 0 and tuple t at k = 2t (the last tuple of an odd rational CF at k = n)
+/// For irrational input, --tuples N keeps the quotients a_0..a_2K of the first N tuples
+pub fn run(cf: &[Integer], complete: bool, args: &Args) {
+    if args.silent {
+        return;
+    }
+    let first = if cf.first().is_some_and(|a| !a.is_zero()) { 0 } else { 1 };
+    let cf = match args.tuples {
+        Some(n) if !complete => &cf[..cf.len().min(2 * (n + first).saturating_sub(1) + 1)],
+        _ => cf,
+    };
+    if args.cf {
+        for a in cf {
+            println!("{}", a);
+        }
+        return;
+    }
+    let mut tuples = vec![None; cf.len()];
+    if args.with_tuples {
+        let mut res = vec![];
+        as_egyptian_fraction_symbolic_from_cf(cf, complete, &mut res);
+        let n = cf.len().saturating_sub(1);
+        for (t, tuple) in res.into_iter().enumerate() {
+            tuples[(2 * (t + first)).min(n)] = Some(tuple);
+        }
+    }
+    let m = if args.semiconvergents { "m" } else { "a_k" };
+    let header = if args.with_tuples { "\tu\tv\ti\tj" } else { "" };
+    println!("k\t{}\tp\tq\tbound{}", m, header);
+    for x in approximations(cf, complete, args.semiconvergents) {
+        let bound = x.bound.map_or("0".to_string(), |n| format!("1/{}", n));
+        print!("{}\t{}\t{}\t{}\t{}", x.k, x.m, x.p, x.q, bound);
+        match &tuples[x.k] {
+            Some((u, v, i, j)) if x.m == cf[x.k] => println!("\t{}\t{}\t{}\t{}", u, v, i, j),
+            _ => println!(),
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use rug::{Integer, Rational};
+
+/// Enumerate all representations of x as a sum of at most `max_terms` distinct
+/// unit fractions with denominators ≤ `max_den`.
+/// Representations are emitted in canonical order: denominators increasing within
+/// a representation, representations in lexicographic order. Distinct denominators
+/// are enforced by construction, so no representation is produced twice.
+/// Returns the number of representations passed to `emit`.
+pub fn enumerate_representations<F: FnMut(&[Integer])>(
+    x: &Rational, max_terms: usize, max_den: &Integer, mut emit: F) -> usize
+{
+    if *x <= 0 {
+        return 0;
+    }
+    let mut path = vec![];
+    let mut count = 0;
+    search(x, max_terms, &Integer::from(1), max_den, &mut path, &mut emit, &mut count);
+    count
+}
+
+/// Depth-first search over increasing denominators
+/// For remainder p/q and t terms left, the next denominator d satisfies
+/// q/p ≤ d ≤ t·q/p, since t distinct unit fractions starting at 1/d sum to less than t/d
+fn search<F: FnMut(&[Integer])>(
+    rem: &Rational, terms_left: usize, min_den: &Integer, max_den: &Integer,
+    path: &mut Vec<Integer>, emit: &mut F, count: &mut usize)
+{
+    if rem.is_zero() {
+        emit(path);
+        *count += 1;
+        return;
+    }
+    if terms_left == 0 {
+        return;
+    }
+    let (p, q) = (rem.numer(), rem.denom());
+    if terms_left == 1 {
+        if *p == 1 && q >= min_den && q <= max_den {
+            path.push(q.clone());
+            emit(path);
+            *count += 1;
+            path.pop();
+        }
+        return;
+    }
+    let mut d = (q.clone() + p - 1u32) / p;
+    if d < *min_den {
+        d = min_den.clone();
+    }
+    let mut upper = Integer::from(q * terms_left) / p;
+    if upper > *max_den {
+        upper = max_den.clone();
+    }
+    while d <= upper {
+        let next = rem.clone() - Rational::from((Integer::from(1), d.clone()));
+        path.push(d.clone());
+        search(&next, terms_left - 1, &(d.clone() + 1), max_den, path, emit, count);
+        path.pop();
+        d += 1;
+    }
+}
@@ -0,0 +1,256 @@
+//! Infix expression support: `2^9689 - 1`, `(pi+e)/4`, `fib(100)/fib(101)`, `sqrt(13)`
+//! Expressions are compiled to the RPN token stream understood by the RPN evaluators.
+
+use crate::rpn::parse_number;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(String),
+    Ident(String),
+    Op(char),
+    Postfix(String),
+    LParen,
+    RParen,
+    Comma,
+}
+
+/// Binary operators: (precedence, right associative)
+fn binary(op: char) -> Option<(u8, bool)> {
+    match op {
+        '+' | '-' => Some((1, false)),
+        '*' | '/' => Some((2, false)),
+        '^' => Some((4, true)),
+        _ => None,
+    }
+}
+
+/// Unary minus binds tighter than * and / but looser than ^, so -2^2 = -(2^2)
+const NEG_PRECEDENCE: u8 = 3;
+
+/// Tokens with their 1-based columns
+fn tokenize(s: &str) -> Result<Vec<(Token, usize)>, String> {
+    let chars = s.chars().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            i = number_end(&chars, start);
+            tokens.push((Token::Number(chars[start..i].iter().collect()), start + 1));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push((Token::Ident(chars[start..i].iter().collect()), start + 1));
+        } else if c == '!' {
+            let double = chars.get(i + 1) == Some(&'!');
+            tokens.push((Token::Postfix(if double { "!!" } else { "!" }.to_string()), i + 1));
+            i += if double { 2 } else { 1 };
+        } else {
+            tokens.push((match c {
+                '(' => Token::LParen,
+                ')' => Token::RParen,
+                ',' => Token::Comma,
+                '+' | '-' | '*' | '/' | '^' => Token::Op(c),
+                _ => return Err(format!("unexpected character '{}' at column {}", c, i + 1)),
+            }, i + 1));
+            i += 1;
+        }
+    }
+    Ok(tokens)
+}
+
+/// End of the numeric literal starting at `start`: alphanumerics and '.', an exponent sign
+/// (`1e-5`) and a repeating group after the point (`0.1(6)`) are part of the literal
+fn number_end(chars: &[char], start: usize) -> usize {
+    let radix = chars[start] == '0' && matches!(chars.get(start + 1), Some('x' | 'X' | 'o' | 'O' | 'b' | 'B'));
+    let mut i = start;
+    loop {
+        match chars.get(i) {
+            Some(c) if c.is_ascii_alphanumeric() || *c == '.' => i += 1,
+            Some('+' | '-') if !radix && matches!(chars[i - 1], 'e' | 'E') => i += 1,
+            Some('(') if !radix && chars[start..i].contains(&'.') && !chars[start..i].contains(&'(') => {
+                let digits = chars[i + 1..].iter().take_while(|c| c.is_ascii_digit()).count();
+                if digits == 0 || chars.get(i + 1 + digits) != Some(&')') {
+                    break;
+                }
+                i += digits + 2;
+            }
+            _ => break,
+        }
+    }
+    i
+}
+
+/// Operator stack entries for the shunting-yard algorithm
+#[derive(Debug)]
+enum Pending {
+    Binary(char),
+    Neg,
+    /// Opening parenthesis and its column
+    Paren(usize),
+    Function(String),
+}
+
+/// Compile an infix expression to RPN (space separated tokens), with the column in `s` of each RPN token
+pub fn infix_to_rpn(s: &str) -> Result<(String, Vec<usize>), String> {
+    let tokens = tokenize(s)?;
+    // each RPN token with the column of the infix token it comes from
+    let mut out: Vec<(String, usize)> = vec![];
+    let mut ops: Vec<(Pending, usize)> = vec![];
+    // true when the next token must start an operand (so '-' is unary)
+    let mut expect_operand = true;
+
+    fn emit((p, column): (Pending, usize), out: &mut Vec<(String, usize)>) {
+        match p {
+            Pending::Binary(op) => out.push((op.to_string(), column)),
+            Pending::Neg => {
+                out.push(("-1".to_string(), column));
+                out.push(("*".to_string(), column));
+            }
+            Pending::Function(name) => out.push((name, column)),
+            Pending::Paren(_) => {}
+        }
+    }
+
+    for (k, (token, column)) in tokens.iter().enumerate() {
+        match token {
+            Token::Number(n) => {
+                out.push((n.clone(), *column));
+                expect_operand = false;
+            }
+            Token::Ident(name) => {
+                if tokens.get(k + 1).is_some_and(|(t, _)| *t == Token::LParen) {
+                    if is_aggregate(name) {
+                        // limit the fold to this call's arguments
+                        out.push(("[".to_string(), *column));
+                    }
+                    ops.push((Pending::Function(name.clone()), *column));
+                } else {
+                    out.push((name.clone(), *column));
+                    expect_operand = false;
+                }
+            }
+            Token::Op('-') if expect_operand => ops.push((Pending::Neg, *column)),
+            Token::Op('+') if expect_operand => {}
+            Token::Postfix(op) => {
+                if expect_operand {
+                    return Err(format!("'{}' without operand at column {}", op, column));
+                }
+                out.push((op.clone(), *column));
+            }
+            Token::Op(op) => {
+                if expect_operand {
+                    return Err(format!("operator '{}' without left operand at column {}", op, column));
+                }
+                let (prec, right) = binary(*op).unwrap();
+                while let Some((top, _)) = ops.last() {
+                    let top_prec = match top {
+                        Pending::Binary(t) => binary(*t).unwrap().0,
+                        Pending::Neg => NEG_PRECEDENCE,
+                        _ => break,
+                    };
+                    if top_prec > prec || (top_prec == prec && !right) {
+                        emit(ops.pop().unwrap(), &mut out);
+                    } else {
+                        break;
+                    }
+                }
+                ops.push((Pending::Binary(*op), *column));
+                expect_operand = true;
+            }
+            Token::LParen => {
+                ops.push((Pending::Paren(*column), *column));
+                expect_operand = true;
+            }
+            Token::Comma | Token::RParen => {
+                let open = loop {
+                    match ops.pop() {
+                        Some((Pending::Paren(open), _)) => break open,
+                        Some(p) => emit(p, &mut out),
+                        None => return Err(format!("unmatched ')' or ',' at column {}", column)),
+                    }
+                };
+                if *token == Token::Comma {
+                    // keep the call's parenthesis open for the next argument
+                    ops.push((Pending::Paren(open), open));
+                    expect_operand = true;
+                } else {
+                    if let Some((Pending::Function(_), _)) = ops.last() {
+                        emit(ops.pop().unwrap(), &mut out);
+                    }
+                    expect_operand = false;
+                }
+            }
+        }
+    }
+    while let Some(p) = ops.pop() {
+        if let (Pending::Paren(column), _) = p {
+            return Err(format!("unmatched '(' at column {}", column));
+        }
+        emit(p, &mut out);
+    }
+    if out.is_empty() {
+        return Err("empty expression".to_string());
+    }
+    let (rpn, columns): (Vec<_>, Vec<_>) = out.into_iter().unzip();
+    Ok((rpn.join(" "), columns))
+}
+
+/// Guess whether an expression is infix rather than RPN
+/// Infix if it has parentheses or commas outside number literals, operators glued to operands (`2^10`),
+/// or a binary operator that would underflow the RPN stack (`1 + 2`)
+pub fn is_infix(s: &str) -> bool {
+    let mut depth = 0usize;
+    let mut marks = vec![];
+    for token in s.split_whitespace() {
+        // parentheses inside repeating decimals such as 0.(3) belong to the literal
+        let is_literal = parse_number(token).is_some();
+        if !is_literal && token.contains(['(', ')', ',']) {
+            return true;
+        }
+        if !is_literal && token.len() > 1 && token.contains(['+', '-', '*', '/', '^']) {
+            return true;
+        }
+        if token.contains('!') && !token.chars().all(|c| c == '!') {
+            return true;
+        }
+        match token {
+            "+" | "-" | "*" | "/" | "^" => {
+                if depth < 2 {
+                    return true;
+                }
+                depth -= 1;
+            }
+            "!" | "!!" if depth == 0 => return true,
+            "seq" => depth = usize::MAX / 2,
+            "[" => marks.push(depth),
+            "sum" | "prod" | "lcm" | "gcd" => depth = marks.pop().map_or(1, |m: usize| m.min(depth) + 1),
+            "dup" | "over" => depth += 1,
+            "drop" => depth = depth.saturating_sub(1),
+            // 'x =' pops into x: undo the push counted for the name and pop the value
+            "=" => depth = depth.saturating_sub(2),
+            _ if (is_literal || token.chars().all(|c| c.is_alphanumeric() || c == '_'))
+                && !is_operator_word(token) => depth += 1,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Functions folding all their arguments, bracketed so they don't fold the whole stack
+fn is_aggregate(name: &str) -> bool {
+    matches!(name, "sum" | "prod" | "lcm" | "gcd")
+}
+
+/// RPN words that consume operands rather than push one
+fn is_operator_word(token: &str) -> bool {
+    matches!(token, "seq" | "sum" | "prod" | "lcm" | "inv" | "sqrt" | "isqrt" | "fib" | "p" | "np" | "pp" | "!"
+        | "cbrt" | "root" | "log" | "exp" | "sin" | "cos" | "tan" | "tanh" | "atan" | "zeta" | "gammafn"
+        | "gcd" | "mod" | "div" | "binom" | "powmod" | "!!" | "primorial" | "catnum" | "lucas"
+        | "bernoulli" | "harmonic" | "H" | "numer" | "denom" | "swap")
+}
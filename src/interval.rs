@@ -0,0 +1,221 @@
+//! Closed intervals [lo, hi] of MPFR floats with outward rounding
+//! Every operation rounds lo down and hi up, so the true real value is always enclosed.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use rug::{Float, Integer, Rational};
+use rug::float::{Constant, Round};
+use rug::ops::{AssignRound, Pow};
+
+#[derive(Debug, Clone)]
+pub struct Interval {
+    pub lo: Float,
+    pub hi: Float,
+}
+
+fn down<T>(precision: u32, val: T) -> Float
+where Float: AssignRound<T, Round = Round, Ordering = Ordering> {
+    Float::with_val_round(precision, val, Round::Down).0
+}
+
+fn up<T>(precision: u32, val: T) -> Float
+where Float: AssignRound<T, Round = Round, Ordering = Ordering> {
+    Float::with_val_round(precision, val, Round::Up).0
+}
+
+/// Smallest and largest of values rounded down and up respectively
+fn hull(lows: Vec<Float>, highs: Vec<Float>) -> Interval {
+    let lo = lows.into_iter().reduce(|a, b| if b < a { b } else { a }).unwrap();
+    let hi = highs.into_iter().reduce(|a, b| if b > a { b } else { a }).unwrap();
+    Interval { lo, hi }
+}
+
+impl Interval {
+    /// Tightest enclosure of an exact rational
+    pub fn exact(r: &Rational, precision: u32) -> Interval {
+        Interval { lo: down(precision, r), hi: up(precision, r) }
+    }
+
+    pub fn constant(c: Constant, precision: u32) -> Interval {
+        Interval { lo: down(precision, c), hi: up(precision, c) }
+    }
+
+    /// The whole real line, for enclosures that cannot be bounded
+    pub fn unbounded(precision: u32) -> Interval {
+        let inf = Float::with_val(precision, rug::float::Special::Infinity);
+        Interval { lo: -inf.clone(), hi: inf }
+    }
+
+    pub fn precision(&self) -> u32 {
+        self.lo.prec().max(self.hi.prec())
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.lo.is_finite() && self.hi.is_finite()
+    }
+
+    fn contains_zero(&self) -> bool {
+        self.lo <= 0 && self.hi >= 0
+    }
+
+    /// Exact rational endpoints
+    pub fn to_rationals(&self) -> (Rational, Rational) {
+        (Rational::try_from(&self.lo).unwrap(), Rational::try_from(&self.hi).unwrap())
+    }
+
+    pub fn add(&self, other: &Interval) -> Interval {
+        let p = self.precision().max(other.precision());
+        Interval { lo: down(p, &self.lo + &other.lo), hi: up(p, &self.hi + &other.hi) }
+    }
+
+    pub fn sub(&self, other: &Interval) -> Interval {
+        let p = self.precision().max(other.precision());
+        Interval { lo: down(p, &self.lo - &other.hi), hi: up(p, &self.hi - &other.lo) }
+    }
+
+    pub fn mul(&self, other: &Interval) -> Interval {
+        let p = self.precision().max(other.precision());
+        let pairs = [(&self.lo, &other.lo), (&self.lo, &other.hi), (&self.hi, &other.lo), (&self.hi, &other.hi)];
+        hull(pairs.iter().map(|(a, b)| down(p, *a * *b)).collect(),
+             pairs.iter().map(|(a, b)| up(p, *a * *b)).collect())
+    }
+
+    pub fn div(&self, other: &Interval) -> Result<Interval, String> {
+        if other.contains_zero() {
+            return Err("division by an interval containing zero".to_string());
+        }
+        let p = self.precision().max(other.precision());
+        let pairs = [(&self.lo, &other.lo), (&self.lo, &other.hi), (&self.hi, &other.lo), (&self.hi, &other.hi)];
+        Ok(hull(pairs.iter().map(|(a, b)| down(p, *a / *b)).collect(),
+                pairs.iter().map(|(a, b)| up(p, *a / *b)).collect()))
+    }
+
+    /// Integer power: monotone pieces on each side of zero
+    pub fn pow(&self, n: &Integer) -> Result<Interval, String> {
+        let p = self.precision();
+        if *n < 0 {
+            let one = Interval::exact(&Rational::from(1), p);
+            return one.div(&self.pow(&Integer::from(-n))?);
+        }
+        let (lo_down, lo_up) = (down(p, (&self.lo).pow(n)), up(p, (&self.lo).pow(n)));
+        let (hi_down, hi_up) = (down(p, (&self.hi).pow(n)), up(p, (&self.hi).pow(n)));
+        Ok(if n.is_odd() || self.lo >= 0 {
+            Interval { lo: lo_down, hi: hi_up }
+        } else if self.hi <= 0 {
+            Interval { lo: hi_down, hi: lo_up }
+        } else {
+            Interval { lo: Float::new(p), hi: if lo_up > hi_up { lo_up } else { hi_up } }
+        })
+    }
+
+    /// Real power x^y = exp(y·ln x) for x > 0
+    pub fn powf(&self, y: &Interval) -> Result<Interval, String> {
+        Ok(y.mul(&self.ln()?).exp())
+    }
+
+    pub fn sqrt(&self) -> Result<Interval, String> {
+        if self.lo < 0 {
+            return Err("square root of an interval reaching below zero".to_string());
+        }
+        let p = self.precision();
+        Ok(Interval { lo: down(p, self.lo.sqrt_ref()), hi: up(p, self.hi.sqrt_ref()) })
+    }
+
+    /// n-th root, increasing; odd roots of negative numbers are real
+    pub fn root(&self, n: u32) -> Result<Interval, String> {
+        if n.is_multiple_of(2) && self.lo < 0 {
+            return Err("even root of an interval reaching below zero".to_string());
+        }
+        let p = self.precision();
+        Ok(Interval { lo: down(p, self.lo.root_ref(n)), hi: up(p, self.hi.root_ref(n)) })
+    }
+
+    pub fn ln(&self) -> Result<Interval, String> {
+        if self.lo <= 0 {
+            return Err("logarithm of an interval reaching zero or below".to_string());
+        }
+        let p = self.precision();
+        Ok(Interval { lo: down(p, self.lo.ln_ref()), hi: up(p, self.hi.ln_ref()) })
+    }
+
+    pub fn exp(&self) -> Interval {
+        let p = self.precision();
+        Interval { lo: down(p, self.lo.exp_ref()), hi: up(p, self.hi.exp_ref()) }
+    }
+
+    pub fn atan(&self) -> Interval {
+        let p = self.precision();
+        Interval { lo: down(p, self.lo.atan_ref()), hi: up(p, self.hi.atan_ref()) }
+    }
+
+    pub fn tanh(&self) -> Interval {
+        let p = self.precision();
+        Interval { lo: down(p, self.lo.tanh_ref()), hi: up(p, self.hi.tanh_ref()) }
+    }
+
+    /// tan is increasing between its poles; only certified inside (-1.57, 1.57) ⊂ (-π/2, π/2)
+    pub fn tan(&self) -> Result<Interval, String> {
+        if self.lo <= -1.57 || self.hi >= 1.57 {
+            return Err("tan of an interval is only certified inside (-1.57, 1.57)".to_string());
+        }
+        let p = self.precision();
+        Ok(Interval { lo: down(p, self.lo.tan_ref()), hi: up(p, self.hi.tan_ref()) })
+    }
+
+    /// sin and cos are 1-Lipschitz: f([m - r, m + r]) ⊆ [f(m) - r, f(m) + r], clipped to [-1, 1]
+    pub fn sin(&self) -> Interval {
+        let (m, r) = self.mid_radius();
+        let p = self.precision();
+        self.lipschitz(down(p, m.sin_ref()), up(p, m.sin_ref()), &r)
+    }
+
+    pub fn cos(&self) -> Interval {
+        let (m, r) = self.mid_radius();
+        let p = self.precision();
+        self.lipschitz(down(p, m.cos_ref()), up(p, m.cos_ref()), &r)
+    }
+
+    fn mid_radius(&self) -> (Float, Float) {
+        let p = self.precision();
+        let m = Float::with_val(p, &self.lo + &self.hi) / 2;
+        let r = up(p, &self.hi - &m).max(&up(p, &m - &self.lo));
+        (m, r)
+    }
+
+    fn lipschitz(&self, f_lo: Float, f_hi: Float, r: &Float) -> Interval {
+        let p = self.precision();
+        let lo = down(p, &f_lo - r).max(&Float::with_val(p, -1));
+        let hi = up(p, &f_hi + r).min(&Float::with_val(p, 1));
+        Interval { lo, hi }
+    }
+
+    /// ζ is decreasing on (1, ∞); other arguments are only certified as points
+    pub fn zeta(&self) -> Result<Interval, String> {
+        let p = self.precision();
+        if self.lo == self.hi {
+            Ok(Interval { lo: down(p, self.lo.zeta_ref()), hi: up(p, self.lo.zeta_ref()) })
+        } else if self.lo > 1 {
+            Ok(Interval { lo: down(p, self.hi.zeta_ref()), hi: up(p, self.lo.zeta_ref()) })
+        } else {
+            Err("zeta of an interval is only certified above 1".to_string())
+        }
+    }
+
+    /// Γ is increasing on [2, ∞); other arguments are only certified as points
+    pub fn gamma(&self) -> Result<Interval, String> {
+        let p = self.precision();
+        if self.lo == self.hi {
+            Ok(Interval { lo: down(p, self.lo.gamma_ref()), hi: up(p, self.lo.gamma_ref()) })
+        } else if self.lo >= 2 {
+            Ok(Interval { lo: down(p, self.lo.gamma_ref()), hi: up(p, self.hi.gamma_ref()) })
+        } else {
+            Err("gamma of an interval is only certified from 2 upwards".to_string())
+        }
+    }
+}
+
+impl std::fmt::Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{:.12}, {:.12}]", self.lo, self.hi)
+    }
+}
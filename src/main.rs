@@ -1,15 +1,31 @@
+mod algebraic;
+mod approx;
+mod cf;
+mod convergents;
+mod enumerate;
+mod infix;
+mod interval;
+mod portfolio;
+mod quadratic;
 mod rpn;
+mod unity;
 
-use crate::rpn::{_parse_rpn, _parse_rpn_irrational};
+use crate::algebraic::{format_polynomial, Root, RootCf};
+use crate::cf::{Bihomographic, CfSource, Finite, Periodic, Term};
+use crate::enumerate::enumerate_representations;
+use crate::quadratic::{PeriodicCf, Quad};
+use crate::portfolio::Metric;
+use crate::rpn::{_parse_rpn_integer, evaluate, EvalError, Value};
+use crate::unity::Family;
 
 use std::io;
 use std::ops::{Add, Sub, Div, Mul, SubAssign, Neg};
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use rug::{Complete, Integer, Rational};
 
 /// Egyptian Fractions
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[clap(author, version, about, long_about = None)]
 #[command(arg_required_else_help(true))]
 struct Args {
@@ -21,6 +37,10 @@ struct Args {
     #[clap(short, long, value_parser, default_value_t = false)]
     merge: bool,
 
+    /// Merge algorithm used by --merge
+    #[clap(long, value_enum, default_value_t = MergeStrategy::Greedy)]
+    merge_strategy: MergeStrategy,
+
     /// Output minimal number of raw quadruplets (aka symbolic sums)
     #[clap(long, value_parser, default_value_t = false)]
     raw: bool,
@@ -33,10 +53,27 @@ struct Args {
     #[clap(short, long, value_parser, default_value_t = false)]
     silent: bool,
 
-    /// Batch mode (expects numerator and denominator on each line of stdin)
+    /// Batch mode (expects numerator and optional denominator on each line of stdin)
     #[clap(long, value_parser, default_value_t = false)]
     batch: bool,
 
+    /// For irrational --raw output, continue past the certified tuples with those of the interval
+    /// midpoint and mark each tuple certified or uncertain
+    ///
+    /// Needs --raw: the marks belong to tuples, which the default output splits and reorders into
+    /// unit fractions. Not available with --best-of, whose candidates regroup the tuples the same way.
+    #[clap(long, value_parser, default_value_t = false, requires = "raw", conflicts_with = "best_of")]
+    stability: bool,
+
+    /// With --stability, stop at the first uncertain tuple
+    #[clap(long, value_parser, default_value_t = false, requires = "stability")]
+    stable_only: bool,
+
+    /// Read raw tuples "u v i j" from stdin and use their exact sum as input;
+    /// reports the CF and whether the tuples are canonical (exit code 1 if not)
+    #[clap(long, value_parser, default_value_t = false, conflicts_with_all = ["batch", "algebraic"])]
+    from_raw: bool,
+
     #[clap(value_parser, default_value_t = String::from("1"))]
     numerator: String,
 
@@ -44,7 +81,7 @@ struct Args {
     denominator: String,
 
     /// Maximum number of terms for breaking large symbolic sums
-    #[clap(short, long, value_parser, default_value_t = 8)]
+    #[clap(short, long, value_parser, default_value_t = DEFAULT_LIMIT)]
     limit: usize,
 
     /// Precision in bits for irrational/transcendental constants (pi, e, phi, etc.)
@@ -52,20 +89,141 @@ struct Args {
     #[clap(short, long, value_parser, default_value_t = 256)]
     precision: u32,
 
-    /// Pell equation mode: output (q, p, norm) for sqrt(D)/1 input
-    /// Finds solutions to p² - D·q² = ±1
+    /// Pell equation mode: output (q, p, norm) and the raw tuples for the convergents of √D up to the
+    /// fundamental solution of p² - D·q² = 1, from the exact CF (D, or taken from a "D sqrt" numerator)
+    #[clap(long, value_parser, num_args = 0..=1, value_name = "D")]
+    pell: Option<Option<String>>,
+
+    /// Generalised Pell equation p² - D·q² = N: the fundamental solution of each class (RPN expression)
+    #[clap(long, value_parser, allow_hyphen_values = true, requires = "pell")]
+    norm: Option<String>,
+
+    /// List every solution of the --norm classes with |q| up to this (RPN expression)
+    #[clap(long, value_parser, requires = "norm")]
+    max_q: Option<String>,
+
+    /// For irrational input, double -p until this many raw tuples are certified (output is cut to N)
+    #[clap(long, value_parser)]
+    tuples: Option<usize>,
+
+    /// For irrational input, double -p until the certified tuples sum to within 10^-N
+    #[clap(long, value_parser)]
+    digits: Option<u32>,
+
+    /// Real algebraic input: integer polynomial coefficients, highest degree first ("1 0 0 -2" = x^3 - 2)
+    #[clap(long, value_parser, allow_hyphen_values = true)]
+    algebraic: Option<String>,
+
+    /// Which real root of --algebraic, counted from the smallest
+    #[clap(long, value_parser, default_value_t = 1, requires = "algebraic")]
+    root: usize,
+
+    /// Output the partial quotients of the (certified) CF, one per line
+    #[clap(long, value_parser, default_value_t = false, conflicts_with = "convergents")]
+    cf: bool,
+
+    /// Output convergents p/q with bounds 1/N on |x - p/q|
     #[clap(long, value_parser, default_value_t = false)]
-    pell: bool,
+    convergents: bool,
+
+    /// Also list the semiconvergents before each convergent
+    #[clap(long, value_parser, default_value_t = false, requires = "convergents")]
+    semiconvergents: bool,
+
+    /// Show each convergent next to the raw tuple completing it (CF-Egypt bijection)
+    #[clap(long, value_parser, default_value_t = false, requires = "convergents")]
+    with_tuples: bool,
+
+    /// Replace the input by its best approximation p/q with q at most this (RPN expression)
+    #[clap(long, value_parser, conflicts_with = "pell")]
+    best_approx: Option<String>,
+
+    /// Restrict --best-approx to denominators coprime to this modulus (RPN expression)
+    #[clap(long, value_parser, requires = "best_approx")]
+    coprime: Option<String>,
+
+    /// Enumerate all representations with at most this many distinct unit fractions
+    #[clap(long, value_parser, requires = "max_denominator")]
+    enumerate: Option<usize>,
+
+    /// Largest denominator allowed by --enumerate (RPN expression)
+    #[clap(long, value_parser)]
+    max_denominator: Option<String>,
+
+    /// Only print the number of representations found by --enumerate
+    #[clap(long, value_parser, default_value_t = false)]
+    count: bool,
+
+    /// Generate and verify a structured representation of 1;
+    /// the numerator gives the parameter (terms n, tuple size k or perfect number N)
+    #[clap(long, value_enum)]
+    unity: Option<Family>,
+
+    /// Try --limit/--merge/--reverse configurations in parallel and keep the best by this metric
+    ///
+    /// Not available with --raw: the metrics score the denominators of unit fractions.
+    #[clap(long, value_enum, conflicts_with = "raw")]
+    best_of: Option<Metric>,
+
+    /// Treat expressions as RPN (disables infix auto-detection)
+    #[clap(long, value_parser, default_value_t = false, conflicts_with = "infix")]
+    rpn: bool,
+
+    /// Treat expressions as infix, e.g. '2^9689 - 1' or 'fib(100)/fib(101)'
+    #[clap(long, value_parser, default_value_t = false)]
+    infix: bool,
+
+    /// File of RPN word definitions, Forth style: ': sq dup * ;'
+    #[clap(long, value_parser)]
+    defs: Option<String>,
 }
 
-fn merge(eg: &Vec<(Integer, Integer, Integer, Integer)>) -> Vec<(Integer, Integer, Integer, Integer)> {
+/// Merge algorithm used by --merge
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum MergeStrategy {
+    /// Left to right, grabbing the furthest run summing to a unit fraction
+    Greedy,
+    /// Minimum number of contiguous runs summing to unit fractions
+    Optimal,
+    /// Minimum number of arbitrary groups summing to unit fractions (up to 18 terms)
+    Subset,
+}
+
+/// Default --limit: maximum number of terms of a symbolic sum before it is halved
+const DEFAULT_LIMIT: usize = 8;
+
+/// Largest input for which the O(3^n) subset merge is attempted
+const SUBSET_MERGE_MAX_TERMS: usize = 18;
+
+/// Backtracking budget when looking for a minimal merge with distinct terms
+const DISTINCT_MERGE_BUDGET: usize = 100_000;
+
+/// Tuples produced for exact infinite expansions (--algebraic, combined surds)
+/// when neither --tuples nor --digits is given
+const EXACT_TUPLES: usize = 10;
+
+/// Largest precision in bits tried when doubling for --tuples, --digits or --best-approx
+const MAX_PRECISION: u32 = 1 << 22;
+
+fn merge_with(eg: &[(Integer, Integer, Integer, Integer)], strategy: MergeStrategy) -> Vec<(Integer, Integer, Integer, Integer)> {
+    match strategy {
+        MergeStrategy::Greedy => merge(eg),
+        MergeStrategy::Optimal => merge_optimal(eg),
+        MergeStrategy::Subset if eg.len() <= SUBSET_MERGE_MAX_TERMS => merge_subset(eg),
+        MergeStrategy::Subset => {
+            eprintln!("# subset merge limited to {} terms, using optimal contiguous merge", SUBSET_MERGE_MAX_TERMS);
+            merge_optimal(eg)
+        }
+    }
+}
+
+fn merge(eg: &[(Integer, Integer, Integer, Integer)]) -> Vec<(Integer, Integer, Integer, Integer)> {
     let mut i = 0_usize;
     let mut ret = vec![];
     while i < eg.len() {
         let mut q = Rational::from((eg[i].0.clone(), eg[i].1.clone()));
         let (mut ones_i, mut ones_q) = (i, q.clone());
-        for j in (i + 1)..eg.len() {
-            let p = &eg[j];
+        for (j, p) in eg.iter().enumerate().skip(i + 1) {
             q += Rational::from((p.0.clone(), p.1.clone()));
             if q.numer() == &Integer::from(1) {
                 (ones_i, ones_q) = (j, q.clone());
@@ -80,6 +238,114 @@ fn merge(eg: &Vec<(Integer, Integer, Integer, Integer)>) -> Vec<(Integer, Intege
     ret
 }
 
+/// Optimal contiguous merge via dynamic programming
+/// best[i] = min over j < i of best[j] + 1, where eg[j..i] is a single term or sums to 1/m.
+/// Among all minimal partitions, one with pairwise distinct sums is preferred.
+fn merge_optimal(eg: &[(Integer, Integer, Integer, Integer)]) -> Vec<(Integer, Integer, Integer, Integer)> {
+    let n = eg.len();
+    let mut best = vec![usize::MAX; n + 1];
+    let mut preds: Vec<Vec<(usize, Rational)>> = vec![vec![]; n + 1];
+    best[0] = 0;
+    for i in 1..=n {
+        let mut q = Rational::new();
+        for j in (0..i).rev() {
+            q += Rational::from((eg[j].0.clone(), eg[j].1.clone()));
+            if best[j] == usize::MAX || (j + 1 < i && q.numer() != &1) {
+                continue;
+            }
+            if best[j] + 1 < best[i] {
+                best[i] = best[j] + 1;
+                preds[i].clear();
+            }
+            if best[j] + 1 == best[i] {
+                preds[i].push((j, q.clone()));
+            }
+        }
+    }
+    let mut groups = vec![];
+    let mut budget = DISTINCT_MERGE_BUDGET;
+    if !distinct_partition(&preds, n, &mut groups, &mut budget) {
+        // No distinct minimal partition found: take any, fix_duplicates handles the rest
+        groups.clear();
+        let mut i = n;
+        while i > 0 {
+            let (j, q) = preds[i][0].clone();
+            groups.push(q);
+            i = j;
+        }
+    }
+    groups.into_iter().rev().map(|q| {
+        let (x, y) = q.into_numer_denom();
+        (x, y, Integer::from(0), Integer::from(0))
+    }).collect()
+}
+
+/// Walk minimal partitions backwards from position i, keeping group sums pairwise distinct
+fn distinct_partition(preds: &[Vec<(usize, Rational)>], i: usize, groups: &mut Vec<Rational>, budget: &mut usize) -> bool {
+    if i == 0 {
+        return true;
+    }
+    for (j, q) in preds[i].iter() {
+        if *budget == 0 {
+            return false;
+        }
+        *budget -= 1;
+        if groups.contains(q) {
+            continue;
+        }
+        groups.push(q.clone());
+        if distinct_partition(preds, *j, groups, budget) {
+            return true;
+        }
+        groups.pop();
+    }
+    false
+}
+
+/// Non-contiguous merge: minimum number of groups of arbitrary terms summing to unit fractions
+/// Exact subset DP in O(3^n): cost[mask] = min over unit-sum submasks s containing the
+/// lowest set bit of cost[mask ^ s] + 1
+fn merge_subset(eg: &[(Integer, Integer, Integer, Integer)]) -> Vec<(Integer, Integer, Integer, Integer)> {
+    let n = eg.len();
+    let full = (1usize << n) - 1;
+    let mut sums = vec![Rational::new(); full + 1];
+    let mut unit = vec![false; full + 1];
+    for mask in 1..=full {
+        let low = mask.trailing_zeros() as usize;
+        sums[mask] = &sums[mask & (mask - 1)] + Rational::from((eg[low].0.clone(), eg[low].1.clone()));
+        unit[mask] = mask.is_power_of_two() || sums[mask].numer() == &1;
+    }
+    let mut cost = vec![usize::MAX; full + 1];
+    let mut choice = vec![0usize; full + 1];
+    cost[0] = 0;
+    for mask in 1..=full {
+        let low = mask & mask.wrapping_neg();
+        let rest = mask ^ low;
+        // enumerate submasks of rest, each combined with the lowest bit
+        let mut sub = rest;
+        loop {
+            let group = sub | low;
+            if unit[group] && cost[mask ^ group] != usize::MAX && cost[mask ^ group] + 1 < cost[mask] {
+                cost[mask] = cost[mask ^ group] + 1;
+                choice[mask] = group;
+            }
+            if sub == 0 {
+                break;
+            }
+            sub = (sub - 1) & rest;
+        }
+    }
+    let mut ret = vec![];
+    let mut mask = full;
+    while mask != 0 {
+        let (x, y) = sums[choice[mask]].clone().into_numer_denom();
+        ret.push((x, y, Integer::from(0), Integer::from(0)));
+        mask ^= choice[mask];
+    }
+    ret.sort_by(|x, y| x.1.cmp(&y.1));
+    ret
+}
+
 /// Compute CF quotients via single GCD pass: O(log p) instead of O(log² p)
 fn cf_quotients(mut a: Integer, mut b: Integer) -> Vec<Integer> {
     let mut quotients = vec![];
@@ -101,8 +367,8 @@ fn convergent_denominators(cf: &[Integer]) -> Vec<Integer> {
     if cf.len() > 1 {
         qs.push(cf[1].clone());
     }
-    for i in 2..cf.len() {
-        let next = cf[i].clone() * &qs[qs.len() - 1] + &qs[qs.len() - 2];
+    for a in cf.iter().skip(2) {
+        let next = a.clone() * &qs[qs.len() - 1] + &qs[qs.len() - 2];
         qs.push(next);
     }
     qs
@@ -110,7 +376,7 @@ fn convergent_denominators(cf: &[Integer]) -> Vec<Integer> {
 
 /// ModInv-based Egyptian fraction computation (original, faster)
 fn as_egyptian_fraction_symbolic_modinv(x0: &Integer, y0: &Integer, _expand: bool, ret: &mut Vec<(Integer, Integer, Integer, Integer)>) {
-    let gcd = x0.clone().gcd(&y0);
+    let gcd = x0.clone().gcd(y0);
     let mut x = x0.clone().div(&gcd);
     let mut y = y0.clone().div(&gcd);
     if x.ge(&y) {
@@ -131,48 +397,50 @@ fn as_egyptian_fraction_symbolic_modinv(x0: &Integer, y0: &Integer, _expand: boo
 
 /// XGCD-based Egyptian fraction computation using CF-Egypt bijection
 /// Complexity: O(log p) vs O(log² p) for ModInv approach
-/// Required for irrational inputs (provides CF structure for stability analysis)
 fn as_egyptian_fraction_symbolic_cf(x0: &Integer, y0: &Integer, _expand: bool, ret: &mut Vec<(Integer, Integer, Integer, Integer)>) {
-    let gcd = x0.clone().gcd(&y0);
-    let mut x = x0.clone().div(&gcd);
-    let y = y0.clone().div(&gcd);
+    // Get CF quotients in single pass
+    let cf = cf_quotients(x0.clone(), y0.clone());
+    as_egyptian_fraction_symbolic_from_cf(&cf, true, ret)
+}
 
-    // Handle integer part
-    if x.ge(&y) {
-        ret.push((x.clone().div(&y), 0.into(), 0.into(), 0.into()));
-        x.sub_assign(x.clone().div(&y).mul(&y));
+/// Raw tuples from CF quotients [a_0; a_1, ..., a_n] via the CF-Egypt bijection
+/// complete: the quotients are the whole expansion of a rational, so a last tuple closes the sum;
+/// otherwise they are a certified prefix of an irrational and only the tuples it determines are built
+/// Required for irrational inputs (provides CF structure for stability analysis)
+fn as_egyptian_fraction_symbolic_from_cf(cf: &[Integer], complete: bool, ret: &mut Vec<(Integer, Integer, Integer, Integer)>) {
+    if cf.is_empty() {
+        return;
     }
 
-    if x.is_zero() {
-        return;
+    // Handle integer part
+    if !cf[0].is_zero() {
+        ret.push((cf[0].clone(), 0.into(), 0.into(), 0.into()));
     }
 
-    // Get CF quotients in single pass
-    let cf = cf_quotients(x.clone(), y.clone());
-    // n = number of partial quotients (CF length excluding leading 0)
+    // n = number of partial quotients (CF length excluding a_0)
     let n = cf.len() - 1;
 
     if n == 0 {
-        // CF = [0], meaning x = 0 (should have returned earlier)
+        // CF = [a_0], no fractional part
         return;
     }
 
-    if n == 1 {
-        // CF = [0; a_1], meaning x/y = 1/y → unit fraction
-        // Raw tuple (1, y-1, 1, 1): T = 1/((1)(1+y-1)) = 1/y
-        ret.push((Integer::from(1), y.clone() - 1, Integer::from(1), Integer::from(1)));
+    if n == 1 && complete {
+        // CF = [a_0; a_1], fractional part 1/a_1 → unit fraction
+        // Raw tuple (1, a_1-1, 1, 1): T = 1/((1)(1+a_1-1)) = 1/a_1
+        ret.push((Integer::from(1), cf[1].clone() - 1, Integer::from(1), Integer::from(1)));
         return;
     }
 
     // Build convergent denominators
-    let qs = convergent_denominators(&cf);
-    let num_tuples = (n + 1) / 2;
+    let qs = convergent_denominators(cf);
+    let num_tuples = if complete { n.div_ceil(2) } else { n / 2 };
 
     // Apply CF-Egypt bijection formula (0-based indexing)
     // Formula: u_k = q_{2k-2}, v_k = q_{2k-1}, j_k = a_{2k}
     // In 0-based: u = qs[2k-2], v = qs[2k-1], j = cf[2k] (since cf[i] = a_i)
     for k in 1..=num_tuples {
-        if k < num_tuples || n % 2 == 0 {
+        if k < num_tuples || n.is_multiple_of(2) || !complete {
             // Regular case: (q_{2k-2}, q_{2k-1}, 1, a_{2k})
             let u = qs[2 * k - 2].clone();
             let v = qs[2 * k - 1].clone();
@@ -192,12 +460,7 @@ fn as_egyptian_fraction_symbolic(x0: &Integer, y0: &Integer, expand: bool, ret:
     as_egyptian_fraction_symbolic_modinv(x0, y0, expand, ret)
 }
 
-/// CF version - use when stability analysis needed (irrationals)
-fn as_egyptian_fraction_symbolic_for_irrational(x0: &Integer, y0: &Integer, expand: bool, ret: &mut Vec<(Integer, Integer, Integer, Integer)>) {
-    as_egyptian_fraction_symbolic_cf(x0, y0, expand, ret)
-}
-
-fn expand(eg: &Vec<(Integer, Integer, Integer, Integer)>) -> Vec<(Integer, Integer, Integer, Integer)> {
+fn expand(eg: &[(Integer, Integer, Integer, Integer)]) -> Vec<(Integer, Integer, Integer, Integer)> {
     let mut ret = vec![];
     for (b,v,i,j) in eg.iter() {
         if v.is_zero() && i.is_zero() && j.is_zero() {
@@ -207,7 +470,7 @@ fn expand(eg: &Vec<(Integer, Integer, Integer, Integer)>) -> Vec<(Integer, Integ
                 ret.push((
                     Integer::from(1),
                     b.clone().sub(v).add(v.clone().mul(&k))
-                        .mul(b.add(v.clone().mul(k.clone()))),
+                        .mul(b.add(v.clone().mul(k))),
                     Integer::from(0),
                     Integer::from(0)
                 ));
@@ -220,8 +483,8 @@ fn expand(eg: &Vec<(Integer, Integer, Integer, Integer)>) -> Vec<(Integer, Integ
 fn as_egyptian_fraction(a:&Integer, b:&Integer, args: &Args)->Vec<(Integer, Integer,Integer,Integer)> {
     let mut res = vec![];
     as_egyptian_fraction_symbolic(
-        &a,
-        &b,
+        a,
+        b,
         args.reverse, &mut res);
     let limit = args.limit.max(2);
     if !args.raw {
@@ -232,7 +495,7 @@ fn as_egyptian_fraction(a:&Integer, b:&Integer, args: &Args)->Vec<(Integer, Inte
             if !args.reverse {
                 res.reverse();
             }
-            res = merge(&res);
+            res = merge_with(&res, args.merge_strategy);
         }
         res = fix_duplicates(&res);
     } else {
@@ -243,11 +506,14 @@ fn as_egyptian_fraction(a:&Integer, b:&Integer, args: &Args)->Vec<(Integer, Inte
     res
 }
 
-/// Egyptian fraction for irrationals: uses CF, sorts by fraction size
-/// No truncation - user controls precision, we output full CF expansion
-fn as_egyptian_fraction_irrational(a: &Integer, b: &Integer, args: &Args) -> Vec<(Integer, Integer, Integer, Integer)> {
+/// Egyptian fraction for irrationals: uses the certified CF prefix, sorts by fraction size
+/// Only tuples fixed by certified quotients are produced; -p controls how many there are
+fn as_egyptian_fraction_irrational(cf: &[Integer], args: &Args) -> Vec<(Integer, Integer, Integer, Integer)> {
     let mut res = vec![];
-    as_egyptian_fraction_symbolic_for_irrational(&a, &b, args.reverse, &mut res);
+    as_egyptian_fraction_symbolic_from_cf(cf, false, &mut res);
+    if let Some(n) = args.tuples {
+        res.truncate(n);
+    }
 
     // Sort by unit fraction size (large → small)
     sort_by_fraction_size(&mut res);
@@ -261,7 +527,7 @@ fn as_egyptian_fraction_irrational(a: &Integer, b: &Integer, args: &Args) -> Vec
             if !args.reverse {
                 res.reverse();
             }
-            res = merge(&res);
+            res = merge_with(&res, args.merge_strategy);
         }
         res = fix_duplicates(&res);
     } else {
@@ -272,19 +538,18 @@ fn as_egyptian_fraction_irrational(a: &Integer, b: &Integer, args: &Args) -> Vec
     res
 }
 
-fn fix_duplicates(eg: &Vec<(Integer, Integer, Integer, Integer)>)
+fn fix_duplicates(eg: &[(Integer, Integer, Integer, Integer)])
     -> Vec<(Integer, Integer, Integer, Integer)> {
       if eg.is_empty() {
-          return eg.clone();
+          return eg.to_vec();
       }
-    let mut last_i = 0;
-    let mut eg = eg.clone();
-    while last_i < eg.len() {
+    let mut eg = eg.to_vec();
+    loop {
         eg.sort_by(|x, y| { y.1.cmp(&x.1)});
         let mut ret = vec![];
         let mut cnt = 1;
         let mut prev = eg.first().unwrap();
-        last_i = eg.len();
+        let mut last_i = eg.len();
         for (i, current) in eg.iter().enumerate().skip(1) {
             if current == prev {
                 cnt += 1;
@@ -297,7 +562,8 @@ fn fix_duplicates(eg: &Vec<(Integer, Integer, Integer, Integer)>)
                 prev = current;
             }
         }
-        if last_i < eg.len() {
+        // a run of cnt equal terms, also when it ends the list
+        if cnt > 1 {
             let a = Integer::from(cnt);
             let b = prev.clone();
             let gcd = a.clone().gcd(&b.1);
@@ -325,15 +591,14 @@ fn calculate_raw_sum(u:&Integer, v:&Integer, i:&Integer, j:&Integer) -> (Integer
     (num.div(&gcd), den.div(&gcd))
 }
 
-fn halve_symbolic_sums(a: &Vec<(Integer, Integer, Integer, Integer)>, limit: usize)
+fn halve_symbolic_sums(a: &[(Integer, Integer, Integer, Integer)], limit: usize)
     -> Vec<(Integer, Integer, Integer, Integer)>
 {
-    let mut stack = a.clone();
+    let mut stack = a.to_vec();
     let mut ret = vec![] ;
     let limit = Integer::from(limit);
     let two = Integer::from(2);
-    while !stack.is_empty() {
-        let (u, v, i, j) = stack.pop().unwrap();
+    while let Some((u, v, i, j)) = stack.pop() {
         let term_count = j.clone().sub(&i).add(&Integer::from(1));
         if term_count.le(&limit) {
             ret.push((u, v, i, j));
@@ -354,87 +619,388 @@ fn halve_symbolic_sums(a: &Vec<(Integer, Integer, Integer, Integer)>, limit: usi
     }
     ret
 }
-/// Pell equation solver: extract (q, p, norm) from Egypt tuples
-/// Tuples encode q_{2k-2}, q_{2k-1} as u, v parameters
-/// Returns: Vec<(q, p, norm)> where norm = p² - D·q²
-fn solve_pell(tuples: &[(Integer, Integer, Integer, Integer)], d: &Integer) -> Vec<(Integer, Integer, Integer)> {
-    let mut results = vec![];
 
-    // Extract integer part (a0) and q values from tuples
-    let mut a0 = Integer::from(0);
-    let mut qs: Vec<Integer> = vec![];
+/// Input expression as RPN; for infix input also the source and the column in it of each RPN token
+struct Expression {
+    rpn: String,
+    infix: Option<(String, Vec<usize>)>,
+}
 
-    for (u, v, i, j) in tuples {
-        if v.is_zero() && i.is_zero() && j.is_zero() {
-            // Integer part tuple
-            a0 = u.clone();
-        } else {
-            // Fractional tuple: u = q_{2k-2}, v = q_{2k-1}
-            if qs.is_empty() || qs.last() != Some(u) {
-                qs.push(u.clone());
+impl Expression {
+    /// Evaluate the RPN, locating errors in the expression as typed
+    fn evaluate(&self, precision: u32) -> Result<Value, EvalError> {
+        evaluate(&self.rpn, precision).map_err(|e| match &self.infix {
+            Some((source, columns)) => e.in_source(source, columns),
+            None => e,
+        })
+    }
+}
+
+/// Convert an input expression to RPN, honouring --rpn/--infix or auto-detecting infix syntax
+fn to_rpn(s: &str, args: &Args) -> Result<Expression, String> {
+    if args.rpn || (!args.infix && !infix::is_infix(s)) {
+        return Ok(Expression { rpn: s.to_string(), infix: None });
+    }
+    let (rpn, columns) = infix::infix_to_rpn(s).map_err(|e| format!("cannot parse infix expression '{}': {}", s, e))?;
+    Ok(Expression { rpn, infix: Some((s.to_string(), columns)) })
+}
+
+/// Parsed input: (numerator, denominator, certified CF quotients for irrational input)
+type Parsed = (Integer, Integer, Option<Vec<Integer>>);
+
+/// Parse numerator and denominator expressions (infix or RPN)
+/// Irrational input is re-evaluated at doubling precision until `done` accepts its certified CF
+/// Returns the parsed input and the precision used, None when the input was handled exactly
+fn parse_input<F: Fn(&[Integer]) -> bool>(num_str: &str, den_str: &str, args: &Args, done: F) -> Result<(Parsed, Option<u32>), String> {
+    let (num_rpn, den_rpn) = (to_rpn(num_str, args)?, to_rpn(den_str, args)?);
+    let mut precision = args.precision;
+    loop {
+        let a = num_rpn.evaluate(precision).map_err(|e| format!("in numerator: {}", e))?;
+        let b = den_rpn.evaluate(precision).map_err(|e| format!("in denominator: {}", e))?;
+        if b.to_rational().is_some_and(|y| y.is_zero()) {
+            return Err("denominator is zero".to_string());
+        }
+        if let Some(x) = rpn::quadratic_quotient(&a, &b) {
+            return Ok((parse_quadratic(&x.abs(), args, &done), None));
+        }
+        if let (Some(x), Some(y)) = (a.to_rational(), b.to_rational()) {
+            let (num, den) = (x / y).abs().into_numer_denom();
+            return Ok(((num, den, None), None));
+        }
+        // Divide the CF streams of A and B lazily (Gosper), so no rounding compounds: exact
+        // streams yield quotients on demand, intervals as far as their certified prefixes decide
+        let exact = ![&a, &b].iter().any(|v| matches!(v, Value::Interval(_)));
+        let mut z = Bihomographic::div(cf_source(&a), cf_source(&b)).abs();
+        let target = exact_target(args);
+        let reached = |cf: &[Integer]| done(cf) && target_reached(cf, &target);
+        let mut cf = vec![];
+        // exact streams are checked at doubling lengths, so long expansions stay linear
+        let mut check = 1;
+        while !(exact && cf.len() == check && reached(&cf)) {
+            if exact && cf.len() == check {
+                check *= 2;
             }
-            qs.push(v.clone());
+            match z.next_term() {
+                Term::Quotient(q) => cf.push(q),
+                Term::End => {
+                    let (num, den) = cf_value(&cf).into_numer_denom();
+                    return Ok(((num, den, None), None));
+                }
+                Term::Unknown => break,
+            }
+        }
+        if exact {
+            // an exact stream that never settles: a combination with a rational value
+            if !reached(&cf) {
+                return Err("no quotient could be decided (is the value rational?)".to_string());
+            }
+            // cut back to the shortest prefix that meets the target, which is longer than the last failed check
+            let (mut lo, mut hi) = (check / 2, cf.len());
+            while hi - lo > 1 {
+                let mid = (lo + hi) / 2;
+                if reached(&cf[..mid]) {
+                    hi = mid;
+                } else {
+                    lo = mid;
+                }
+            }
+            cf.truncate(hi);
+        }
+        if exact || done(&cf) {
+            // The last convergent stands in for the value where a fraction is needed
+            let (num, den) = if cf.is_empty() { (Integer::new(), Integer::from(1)) } else { cf_value(&cf).into_numer_denom() };
+            return Ok(((num, den, Some(cf)), (!exact).then_some(precision)));
+        }
+        if precision >= MAX_PRECISION {
+            return Err(format!("target not reached at {} bits precision", precision));
         }
+        precision *= 2;
     }
+}
 
-    if a0.is_zero() {
-        return results;
+/// CF stream of an evaluated value: exact for rationals and quadratic irrationals,
+/// the certified prefix for intervals
+fn cf_source(x: &Value) -> Box<dyn CfSource> {
+    match x {
+        Value::Quad(q) => Box::new(Periodic::new(q.periodic_cf())),
+        Value::Stream(expr) => expr.source(),
+        Value::Interval(i) => Box::new(Finite::certified(i)),
+        _ => Box::new(Finite::rational(&x.to_rational().unwrap())),
     }
+}
 
-    // Compute p values using CF recurrence:
-    // p_{-1} = 1, p_0 = a0
-    // p_n = a_n * p_{n-1} + p_{n-2}
-    // where a_n = (q_n - q_{n-2}) / q_{n-1}
-    let mut ps = vec![Integer::from(1), a0.clone()];
+/// --tuples/--digits for exact infinite expansions, EXACT_TUPLES tuples when neither is given
+fn exact_target(args: &Args) -> Args {
+    Args { tuples: args.tuples.or(args.digits.is_none().then_some(EXACT_TUPLES)), ..args.clone() }
+}
 
-    // First: q_0 = 1, p_0 = a0
-    let norm0 = a0.clone().square() - d;
-    results.push((Integer::from(1), a0.clone(), norm0));
+/// Exact periodic CF of a quadratic irrational, expanded until `done` accepts it
+/// Defaults to the pre-period and one period of the tuples (u, v, 1, j), whose j = a_{2k} then repeat
+fn parse_quadratic<F: Fn(&[Integer]) -> bool>(x: &Quad, args: &Args, done: &F) -> Parsed {
+    let periodic = x.periodic_cf();
+    let mut n = periodic.tuple_cover();
+    while !done(&periodic.take(n)) {
+        n *= 2;
+    }
+    let cf = periodic.take(n);
+    if !args.silent {
+        let (p, d, q) = x.pqa_form();
+        report_periodic(&p, &d, &q, &periodic);
+    }
+    // The last convergent stands in for the value where a fraction is needed
+    let (num, den) = cf_value(&cf).into_numer_denom();
+    (num, den, Some(cf))
+}
 
-    for n in 1..qs.len() {
-        let a_n = if n == 1 {
-            qs[1].clone()
-        } else {
-            (qs[n].clone() - &qs[n - 2]) / &qs[n - 1]
-        };
+/// --pell D --norm N: the fundamental solution (smallest |q|) of each class of p² - D·q² = N, or with
+/// --max-q every solution with |q| up to the bound; the fundamental unit generates each class
+/// Returns false when there is no solution
+fn run_pell_norm(d: &Integer, n: &Integer, args: &Args) -> bool {
+    if n.is_zero() {
+        eprintln!("Error: --norm must not be 0");
+        std::process::exit(2);
+    }
+    let max_q = args.max_q.as_ref().map(|b| or_exit(parse_integer(b, args)));
+    let Some((classes, unit)) = quadratic::pell_classes(d, n) else {
+        if !args.silent {
+            eprintln!("# No Pell solution found ({} is a perfect square)", d);
+        }
+        return false;
+    };
+    if !args.silent {
+        eprintln!("# p² - {}·q² = {}: {} class(es), fundamental unit {} + {}·√{}", d, n, classes.len(), unit.0, unit.1, d);
+        println!("class\tq\tp");
+        for (k, x) in classes.iter().enumerate() {
+            let solutions = match &max_q {
+                Some(bound) => quadratic::pell_class_solutions(x, &unit, d, bound),
+                None => vec![x.clone()],
+            };
+            for (p, q) in solutions {
+                println!("{}\t{}\t{}", k + 1, q, p);
+            }
+        }
+        if classes.is_empty() {
+            eprintln!("# No solutions");
+        }
+    }
+    !classes.is_empty()
+}
 
-        let p_n = a_n * &ps[ps.len() - 1] + &ps[ps.len() - 2];
-        ps.push(p_n.clone());
+/// Print (P + √D)/Q with its periodic CF, and the j = a_{2k} of the tuples that repeat
+fn report_periodic(p: &Integer, d: &Integer, q: &Integer, periodic: &PeriodicCf) {
+    eprintln!("# ({} + √{})/{} = {}", p, d, q, periodic);
+    let (start, len) = periodic.tuple_period();
+    let js = (start..start + len).map(|k| periodic.take(2 * k + 1)[2 * k].to_string()).collect::<Vec<_>>();
+    eprintln!("# tuples {}.. repeat j = ({})", start, js.join(", "));
+}
 
-        let q = qs[n].clone();
-        let norm = p_n.clone().square() - d * q.clone().square();
-        results.push((q, p_n, norm));
+/// Value of a finite CF
+fn cf_value(cf: &[Integer]) -> Rational {
+    cf.iter().rev().skip(1).fold(Rational::from(cf.last().unwrap()), |acc, a| acc.recip() + a)
+}
+
+/// Root `k` (1 = smallest) of the --algebraic polynomial, its CF expanded exactly until `done`
+/// accepts it and --tuples or --digits is reached (EXACT_TUPLES tuples by default); rational roots are exact
+fn parse_algebraic<F: Fn(&[Integer]) -> bool>(coeffs: &str, k: usize, args: &Args, done: F) -> Result<Parsed, String> {
+    let p = algebraic::parse_polynomial(coeffs)?;
+    let roots = algebraic::real_roots(&p);
+    let exact = |x: Rational| {
+        let (num, den) = x.abs().into_numer_denom();
+        (num, den, None)
+    };
+    let (l, r) = match roots.get(k.wrapping_sub(1)) {
+        Some(Root::Rational(x)) => return Ok(exact(x.clone())),
+        Some(Root::Isolated(l, r)) => (l, r),
+        None => return Err(format!("{} has {} real root(s), there is no root {}", format_polynomial(&p), roots.len(), k)),
+    };
+    let mut expansion = match RootCf::new(&p, l, r) {
+        Ok(expansion) => expansion,
+        Err(x) => return Ok(exact(x)),
+    };
+    let target = exact_target(args);
+    let mut cf = vec![];
+    while cf.is_empty() || !(done(&cf) && target_reached(&cf, &target)) {
+        match expansion.next() {
+            Some(a) => cf.push(a),
+            None => return Ok(exact(cf_value(&cf))),
+        }
+    }
+    if !args.silent {
+        let quotients = cf.iter().skip(1).map(|a| a.to_string()).collect::<Vec<_>>();
+        eprintln!("# root {} of {} in ({}, {}) = [{}; {}, ...]", k, format_polynomial(&p), l, r, cf[0], quotients.join(", "));
+    }
+    let (num, den) = cf_value(&cf).into_numer_denom();
+    Ok((num, den, Some(cf)))
+}
+
+/// --stability: the certified tuples continued by those of the midpoint of |A/B| at `bits` precision,
+/// flagged true when certified; --stable-only stops at the first uncertain tuple
+/// The continuation is dropped when the midpoint falls outside the certified prefix
+#[allow(clippy::type_complexity)]
+fn uncertain_tail(cf: &[Integer], certified: &[(Integer, Integer, Integer, Integer)], bits: u32, args: &Args)
+    -> Result<(Vec<(Integer, Integer, Integer, Integer)>, Vec<bool>), String>
+{
+    let a = to_rpn(&args.numerator, args)?.evaluate(bits).map_err(|e| format!("in numerator: {}", e))?.to_interval(bits);
+    let b = to_rpn(&args.denominator, args)?.evaluate(bits).map_err(|e| format!("in denominator: {}", e))?.to_interval(bits);
+    let x = a.div(&b)?;
+    let mut full = cf.to_vec();
+    if x.is_finite() {
+        let (lo, hi) = x.to_rationals();
+        let (num, den) = (Rational::from(&lo + &hi) / 2u32).abs().into_numer_denom();
+        let midpoint = cf_quotients(num, den);
+        if midpoint.starts_with(cf) {
+            full = midpoint;
+        }
     }
+    let mut all = as_egyptian_fraction_irrational(&full, args);
+    let mut flags = all.iter().map(|t| certified.contains(t)).collect::<Vec<_>>();
+    if args.stable_only {
+        let n = flags.iter().position(|&f| !f).unwrap_or(flags.len());
+        all.truncate(n);
+        flags.truncate(n);
+    }
+    Ok((all, flags))
+}
 
-    results
+/// Raw tuples read by --from-raw, one "u v i j" per line (spaces, tabs or commas; '#' starts a comment)
+fn read_raw(lines: impl Iterator<Item = String>) -> Result<Vec<(Integer, Integer, Integer, Integer)>, String> {
+    let mut tuples = vec![];
+    for (line_number, line) in lines.enumerate() {
+        let line = line.split('#').next().unwrap();
+        let fields = line.split(|c: char| c.is_whitespace() || c == ',').filter(|t| !t.is_empty()).collect::<Vec<_>>();
+        if fields.is_empty() {
+            continue;
+        }
+        let numbers = fields.iter().map(|t| t.parse::<Integer>()).collect::<Result<Vec<_>, _>>();
+        match numbers {
+            Ok(x) if x.len() == 4 => tuples.push((x[0].clone(), x[1].clone(), x[2].clone(), x[3].clone())),
+            _ => return Err(format!("line {}: expecting 4 integers u v i j", line_number + 1)),
+        }
+    }
+    Ok(tuples)
 }
 
-/// Check if RPN expression contains irrational/transcendental constants
-fn contains_irrational(s: &str) -> bool {
-    let lower = s.to_lowercase();
-    let trimmed = lower.trim();
-    lower.contains("pi") ||
-    lower.contains(" e ") || lower.starts_with("e ") || lower.ends_with(" e") || trimmed == "e" ||
-    lower.contains("phi") || lower.contains("sqrt2") || lower.contains("gamma") ||
-    lower.contains("sqrt")  // sqrt of non-perfect square
+/// Exact sum of raw tuples, (a, 0, 0, 0) standing for the integer a as in `expand`
+fn raw_sum(tuples: &[(Integer, Integer, Integer, Integer)]) -> Result<Rational, String> {
+    let mut sum = Rational::new();
+    for (k, (u, v, i, j)) in tuples.iter().enumerate() {
+        if v.is_zero() && i.is_zero() && j.is_zero() {
+            sum += u;
+            continue;
+        }
+        let den = Integer::from(u - v) + Integer::from(v * i);
+        if den.is_zero() || (u + Integer::from(v * j)).is_zero() {
+            return Err(format!("tuple {} ({}, {}, {}, {}) has a zero denominator", k + 1, u, v, i, j));
+        }
+        sum += Rational::from(calculate_raw_sum(u, v, i, j));
+    }
+    Ok(sum)
 }
 
-/// Parse RPN, auto-detecting rational vs irrational input
-/// Returns (numerator, denominator, is_irrational)
-fn parse_rpn_auto(num_str: &str, den_str: &str, precision: u32) -> (Integer, Integer, bool) {
-    let is_irrational = contains_irrational(num_str) || contains_irrational(den_str);
-    if is_irrational {
-        // Each irrational expression returns (numerator, denominator) of its rational approximation
-        // For expression A / B, we compute: (num_A / den_A) / (num_B / den_B) = (num_A * den_B) / (den_A * num_B)
-        let (num_a, den_a) = _parse_rpn_irrational(num_str, precision);
-        let (num_b, den_b) = _parse_rpn_irrational(den_str, precision);
-        let final_num = (num_a * &den_b).abs();
-        let final_den = (den_a * &num_b).abs();
-        let gcd = final_num.clone().gcd(&final_den);
-        (final_num / &gcd, final_den / &gcd, true)
+/// --from-raw: sum the tuples on stdin and compare them with the canonical tuples of the sum,
+/// which ModInv and the CF-Egypt bijection both produce (in different orders)
+/// Returns the sum as rational input and whether the tuples were canonical
+fn parse_raw(args: &Args) -> Result<(Parsed, bool), String> {
+    let tuples = read_raw(io::stdin().lines().map_while(Result::ok))?;
+    if tuples.is_empty() {
+        return Err("no raw tuples on stdin".to_string());
+    }
+    let sum = raw_sum(&tuples)?;
+    if sum < 0 {
+        return Err(format!("raw tuples sum to a negative value {}", sum));
+    }
+    let (num, den) = sum.into_numer_denom();
+    let cf = cf_quotients(num.clone(), den.clone());
+    let mut modinv = vec![];
+    as_egyptian_fraction_symbolic_modinv(&num, &den, false, &mut modinv);
+    let mut bijection = vec![];
+    as_egyptian_fraction_symbolic_cf(&num, &den, false, &mut bijection);
+    let (mut sorted, mut canonical) = (tuples.clone(), modinv.clone());
+    sorted.sort();
+    canonical.sort();
+    let form = if tuples == modinv {
+        Some("ModInv order")
+    } else if tuples == bijection {
+        Some("CF order")
+    } else if sorted == canonical {
+        Some("reordered")
     } else {
-        (_parse_rpn(num_str).abs(), _parse_rpn(den_str).abs(), false)
+        None
+    };
+    if !args.silent {
+        let quotients = cf.iter().skip(1).map(|a| a.to_string()).collect::<Vec<_>>();
+        eprintln!("# sum of {} tuples = {}/{} = [{}; {}]", tuples.len(), num, den, cf[0], quotients.join(", "));
+        match form {
+            Some(form) => eprintln!("# canonical ({})", form),
+            None => eprintln!("# not canonical: the canonical form has {} tuples", modinv.len()),
+        }
     }
+    Ok(((num, den, None), form.is_some()))
+}
+
+/// Whether a certified CF satisfies --tuples and --digits
+fn target_reached(cf: &[Integer], args: &Args) -> bool {
+    let tuples_ok = args.tuples.is_none_or(|n| {
+        let mut res = vec![];
+        as_egyptian_fraction_symbolic_from_cf(cf, false, &mut res);
+        res.len() >= n
+    });
+    // The tuples sum to the convergent p_{2K}/q_{2K}, within 1/q_{2K}² of the true value
+    let digits_ok = args.digits.is_none_or(|n| {
+        let last = 2 * ((cf.len().max(1) - 1) / 2);
+        last > 0 && convergent_denominators(cf)[last].clone().square() >= Integer::u_pow_u(10, n).complete()
+    });
+    tuples_ok && digits_ok
+}
+
+/// --pell: convergents of √D up to the fundamental solution, found by the exact integer solver,
+/// next to the raw tuples of √D: every q_{2k-1} is v of tuple k = (q_{2k-2}, q_{2k-1}, 1, a_{2k})
+/// Returns false when D is a perfect square
+fn run_pell(d: &Integer, args: &Args) -> bool {
+    let Some((cf, rows)) = quadratic::pell(d) else {
+        if !args.silent {
+            eprintln!("# No Pell solution found ({} is a perfect square)", d);
+        }
+        return false;
+    };
+    if args.silent {
+        return true;
+    }
+    report_periodic(&Integer::new(), d, &Integer::from(1), &cf);
+    let n = rows.len();
+    let a = cf.take(n + 1);
+    println!("q\tp\tnorm\tu\tv\ti\tj");
+    for (k, (q, p, norm)) in rows.iter().enumerate() {
+        // tuple t = (q_{2t-2}, q_{2t-1}, 1, a_{2t}) sits on the row of its v = q_{2t-1},
+        // tuple 0 = (a_0, 0, 0, 0) on the first row
+        if k == 0 {
+            println!("{}\t{}\t{}\t{}\t0\t0\t0", q, p, norm, a[0]);
+        } else if k % 2 == 1 {
+            println!("{}\t{}\t{}\t{}\t{}\t1\t{}", q, p, norm, rows[k - 1].0, q, a[k + 1]);
+        } else {
+            println!("{}\t{}\t{}", q, p, norm);
+        }
+        if *norm == -1 {
+            eprintln!("# Quasi-solution (norm=-1): p={}, q={}", p, q);
+        }
+    }
+    let (q, p, _) = &rows[n - 1];
+    eprintln!("# Fundamental solution (norm=1): p={}, q={}", p, q);
+    eprintln!("# q = v of tuple {} = ({}, {}, 1, {})", n / 2, rows[n - 2].0, q, a[n]);
+    true
+}
+
+/// Parse an expression (infix or RPN) that must evaluate to an integer
+fn parse_integer(s: &str, args: &Args) -> Result<Integer, String> {
+    to_rpn(s, args)?.evaluate(rpn::DEFAULT_PRECISION).map_err(|e| e.to_string())?.to_integer()
+}
+
+/// Unwrap a parse result or report the error and exit
+fn or_exit<T>(result: Result<T, String>) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(2);
+    })
 }
 
 /// Calculate the smallest denominator a raw tuple produces (for sorting)
@@ -445,7 +1011,7 @@ fn tuple_min_denominator(u: &Integer, v: &Integer) -> Integer {
 }
 
 /// Sort tuples by unit fraction size (large → small = small denominator first)
-fn sort_by_fraction_size(tuples: &mut Vec<(Integer, Integer, Integer, Integer)>) {
+fn sort_by_fraction_size(tuples: &mut [(Integer, Integer, Integer, Integer)]) {
     tuples.sort_by(|a, b| {
         let denom_a = tuple_min_denominator(&a.0, &a.1);
         let denom_b = tuple_min_denominator(&b.0, &b.1);
@@ -456,130 +1022,214 @@ fn sort_by_fraction_size(tuples: &mut Vec<(Integer, Integer, Integer, Integer)>)
 fn main() {
     let args = Args::parse();
 
+    if let Some(path) = &args.defs {
+        let loaded = std::fs::read_to_string(path).map_err(|e| e.to_string())
+            .and_then(|text| rpn::load_definitions(&text));
+        if let Err(e) = loaded {
+            eprintln!("Error: cannot load definitions from '{}': {}", path, e);
+            std::process::exit(2);
+        }
+    }
+
     if args.batch {
-        for line in io::stdin().lines() {
-            if let Ok(line) = line {
-                let num_den = line.split("\t").take(2).collect::<Vec<&str>>();
-                if num_den.len() < 2 {
-                    println!("expecting tab delimited numerator and denominator");
+        for (line_number, line) in io::stdin().lines().map_while(Result::ok).enumerate() {
+            let num_den = line.split("\t").take(2).collect::<Vec<&str>>();
+            if num_den[0].trim().is_empty() {
+                println!("expecting numerator and optional tab delimited denominator");
+                continue;
+            }
+            let den_str = num_den.get(1).copied().unwrap_or("1");
+
+            let ((num, den, cf), _) = match parse_input(num_den[0], den_str, &args, |cf| target_reached(cf, &args)) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    eprintln!("Error on line {}: {}", line_number + 1, e);
                     continue;
                 }
-
-                let (num, den, is_irrational) = parse_rpn_auto(num_den[0], num_den[1], args.precision);
-                let fractions = if is_irrational {
-                    as_egyptian_fraction_irrational(&num, &den, &args)
-                } else {
-                    as_egyptian_fraction(&num, &den, &args)
-                };
-                if !args.silent {
-                    let mut gt0 = false;
-                    print!("{}\t{}\t", num.to_string(), den.to_string());
-                    for (i, (a, b, c, d))
-                        in fractions.iter().enumerate() {
-                        let is_natural = (args.raw && b.is_zero() && c.is_zero() && d.is_zero())
-                            || (!args.raw && *b == Integer::from(1));
-                        if i == 0 && is_natural {
-                            print!("{}\t", a);
-                            gt0 = true;
-                        } else if i == 0 {
-                            if !args.raw {
-                                print!("0\t{}", b);
-                            } else {
-                                print!("0\t{},{},{},{}", a, b, c, d);
-                            }
-                        } else if i == 1 && gt0 {
-                            if args.raw {
-                                print!("{},{},{},{}", a, b, c, d);
-                            } else {
-                                print!("{}", b);
-                            }
+            };
+            let fractions = match &cf {
+                Some(cf) => as_egyptian_fraction_irrational(cf, &args),
+                None => as_egyptian_fraction(&num, &den, &args),
+            };
+            if !args.silent {
+                let mut gt0 = false;
+                print!("{}\t{}\t", num, den);
+                for (i, (a, b, c, d))
+                    in fractions.iter().enumerate() {
+                    let is_natural = (args.raw && b.is_zero() && c.is_zero() && d.is_zero())
+                        || (!args.raw && *b == 1);
+                    if i == 0 && is_natural {
+                        print!("{}\t", a);
+                        gt0 = true;
+                    } else if i == 0 {
+                        if !args.raw {
+                            print!("0\t{}", b);
+                        } else {
+                            print!("0\t{},{},{},{}", a, b, c, d);
+                        }
+                    } else if i == 1 && gt0 {
+                        if args.raw {
+                            print!("{},{},{},{}", a, b, c, d);
                         } else {
-                            if args.raw {
-                                print!(" {},{},{},{}", a, b, c, d);
-                            } else {
-                                print!(" {}", b);
-                            }
+                            print!("{}", b);
+                        }
+                    } else {
+                        if args.raw {
+                            print!(" {},{},{},{}", a, b, c, d);
+                        } else {
+                            print!(" {}", b);
                         }
                     }
-                    print!("\n");
                 }
+                println!();
             }
         }
+    } else if let Some(family) = args.unity {
+        let n = or_exit(parse_integer(&args.numerator, &args));
+        if !or_exit(unity::run(family, &n, &args)) {
+            eprintln!("# Verification failed");
+            std::process::exit(1);
+        }
+    } else if let Some(d) = &args.pell {
+        let d = match d {
+            Some(d) => or_exit(parse_integer(d, &args)),
+            // legacy form: D from a "D sqrt" numerator
+            None => or_exit(to_rpn(&args.numerator, &args).map(|s| extract_pell_d(&s.rpn)))
+                .unwrap_or_else(|| or_exit(Err("--pell requires D, as in 'egypt --pell 13' or 'egypt \"13 sqrt\" --pell'".to_string()))),
+        };
+        if d <= 0 {
+            eprintln!("Error: --pell requires a positive D");
+            std::process::exit(2);
+        }
+        let found = match &args.norm {
+            Some(n) => run_pell_norm(&d, &or_exit(parse_integer(n, &args)), &args),
+            None => run_pell(&d, &args),
+        };
+        if !found {
+            std::process::exit(1);
+        }
     } else {
-        let (num, den, is_irrational) = parse_rpn_auto(&args.numerator, &args.denominator, args.precision);
-
-        // Pell mode: extract D from "D sqrt" pattern
-        if args.pell {
-            // Parse D from numerator (expecting "D sqrt" RPN)
-            let d = extract_pell_d(&args.numerator);
-            if d.is_none() {
-                eprintln!("Error: --pell requires input like 'egypt \"D sqrt\" 1 --pell'");
+        let bound = args.best_approx.as_ref().map(|q| {
+            let q_max = or_exit(parse_integer(q, &args));
+            let modulus = args.coprime.as_ref().map(|m| or_exit(parse_integer(m, &args)));
+            if q_max < 1 || modulus.as_ref().is_some_and(|m| *m < 1) {
+                eprintln!("Error: --best-approx and --coprime must be positive");
                 std::process::exit(2);
             }
-            let d = d.unwrap();
-
-            // Get raw tuples (always use raw mode internally for Pell)
-            let mut res = vec![];
-            as_egyptian_fraction_symbolic_for_irrational(&num, &den, args.reverse, &mut res);
-            sort_by_fraction_size(&mut res);
-
-            // Solve Pell equation
-            let pell_results = solve_pell(&res, &d);
-
-            if !args.silent {
-                println!("q\tp\tnorm");
-                let mut found_fundamental = false;
-                let mut found_quasi = false;
-
-                for (q, p, norm) in pell_results {
-                    println!("{}\t{}\t{}", q, p, norm);
-
-                    if !found_quasi && norm == Integer::from(-1) {
-                        eprintln!("# Quasi-solution (norm=-1): p={}, q={}", p, q);
-                        found_quasi = true;
-                    }
-                    if !found_fundamental && norm == Integer::from(1) {
-                        eprintln!("# Fundamental solution (norm=1): p={}, q={}", p, q);
-                        found_fundamental = true;
-                        break;
-                    }
+            (q_max, modulus)
+        });
+        let done = |cf: &[Integer]| match &bound {
+            Some((q_max, modulus)) => approx::best_approximation(cf, false, q_max, modulus.as_ref()).is_some(),
+            None => target_reached(cf, &args),
+        };
+        let mut canonical = true;
+        let ((num, den, cf), precision) = match &args.algebraic {
+            _ if args.from_raw => {
+                let (parsed, is_canonical) = or_exit(parse_raw(&args));
+                canonical = is_canonical;
+                (parsed, None)
+            }
+            Some(coeffs) => (or_exit(parse_algebraic(coeffs, args.root, &args, done)), None),
+            None => or_exit(parse_input(&args.numerator, &args.denominator, &args, done)),
+        };
+        if let Some(precision) = precision.filter(|&p| p != args.precision || args.tuples.is_some() || args.digits.is_some()) {
+            eprintln!("# precision: {} bits", precision);
+        }
+        // From here on the input is its best approximation, which is rational
+        let (num, den, cf) = match &bound {
+            Some((q_max, modulus)) => {
+                let best = or_exit(match &cf {
+                    Some(cf) => approx::best_approximation(cf, false, q_max, modulus.as_ref()),
+                    None => approx::best_approximation(&cf_quotients(num, den), true, q_max, modulus.as_ref()),
+                }.ok_or_else(|| "the best approximation could not be decided".to_string()));
+                if !args.silent {
+                    let coprime = modulus.as_ref().map_or(String::new(), |m| format!(", gcd(q, {}) = 1", m));
+                    eprintln!("# neighbours with q ≤ {}{}: {} < x < {}", q_max, coprime, best.lo, best.hi);
+                    eprintln!("# best approximation: {}", best.best);
                 }
+                let (num, den) = best.best.into_numer_denom();
+                (num, den, None)
+            }
+            None => (num, den, cf),
+        };
 
-                if !found_fundamental {
-                    if found_quasi {
-                        eprintln!("# No fundamental solution, but quasi-solution exists");
-                    } else {
-                        eprintln!("# No Pell solution found (increase -p precision)");
-                    }
-                    std::process::exit(1);
+        if args.cf || args.convergents {
+            match &cf {
+                Some(cf) => convergents::run(cf, false, &args),
+                None => convergents::run(&cf_quotients(num, den), true, &args),
+            }
+        } else if let Some(max_terms) = args.enumerate {
+            if cf.is_some() {
+                eprintln!("Error: --enumerate requires rational input");
+                std::process::exit(2);
+            }
+            let max_den = or_exit(parse_integer(args.max_denominator.as_deref().unwrap_or("1"), &args));
+            let x = Rational::from((num, den));
+            let count = enumerate_representations(&x, max_terms, &max_den, |dens| {
+                if !args.silent && !args.count {
+                    let line = dens.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+                    println!("{}", line.join(" "));
                 }
+            });
+            if args.count && !args.silent {
+                println!("{}", count);
             }
         } else {
-            let fractions = if is_irrational {
-                as_egyptian_fraction_irrational(&num, &den, &args)
+            let fractions = if let Some(metric) = args.best_of {
+                let (candidates, best) = portfolio::best_of(&num, &den, cf.as_deref(), metric, &args);
+                if !args.silent {
+                    eprintln!("# terms\tmax_den\tbits\tconfiguration");
+                    for (i, c) in candidates.iter().enumerate() {
+                        let mark = if i == best { " *" } else { "" };
+                        eprintln!("# {}\t{}\t{}\t{}{}", c.terms, c.max_den, c.bits, c.label, mark);
+                    }
+                }
+                candidates.into_iter().nth(best).unwrap().fractions
+            } else if let Some(cf) = &cf {
+                as_egyptian_fraction_irrational(cf, &args)
             } else {
                 as_egyptian_fraction(&num, &den, &args)
             };
-            for (a, b, c, d) in fractions.iter() {
+            let certified = match (&cf, precision) {
+                (Some(cf), Some(bits)) if args.stability => Some(or_exit(uncertain_tail(cf, &fractions, bits, &args))),
+                _ => None,
+            };
+            let (fractions, certified) = match certified {
+                Some((all, flags)) => (all, flags),
+                None => {
+                    let n = fractions.len();
+                    (fractions, vec![true; n])
+                }
+            };
+            if let (true, Some(bits)) = (fractions.is_empty() && cf.is_some(), precision) {
+                eprintln!("# No certified tuples at {} bits (increase -p precision)", bits);
+            }
+            for ((a, b, c, d), certified) in fractions.iter().zip(certified) {
                 if !args.silent {
                     if !args.raw {
                         println!("{:?}\t{:?}", a, b);
+                    } else if args.stability {
+                        let status = if certified { "certified" } else { "uncertain" };
+                        println!("{:?}\t{:?}\t{:?}\t{:?}\t{}", a, b, c, d, status);
                     } else {
                         println!("{:?}\t{:?}\t{:?}\t{:?}", a, b, c, d);
                     }
                 }
             }
         }
+        if !canonical {
+            std::process::exit(1);
+        }
     }
 }
 
 /// Extract D from "D sqrt" RPN pattern for Pell mode
 fn extract_pell_d(s: &str) -> Option<Integer> {
-    let parts: Vec<&str> = s.trim().split_whitespace().collect();
+    let parts: Vec<&str> = s.split_whitespace().collect();
     if parts.len() >= 2 && parts.last() == Some(&"sqrt") {
         // Try to parse the number before "sqrt"
         let num_str = parts[..parts.len()-1].join(" ");
-        Some(_parse_rpn(&num_str))
+        _parse_rpn_integer(&num_str).ok()
     } else {
         None
     }
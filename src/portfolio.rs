@@ -0,0 +1,91 @@
+use std::thread;
+use clap::ValueEnum;
+use rug::Integer;
+
+use crate::{as_egyptian_fraction, as_egyptian_fraction_irrational, Args, MergeStrategy};
+
+/// Score used by --best-of to compare configurations (lower is better)
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    /// Number of unit fractions
+    Terms,
+    /// Largest denominator
+    MaxDen,
+    /// Sum of denominator bit lengths
+    Bits,
+    /// Terms, then largest denominator, then bits
+    Lex,
+}
+
+/// Cap on the third --limit that --best-of tries, denominator - 1 (the other two are 2 and 8)
+const MAX_PORTFOLIO_LIMIT: usize = 4096;
+
+/// One evaluated configuration
+pub struct Candidate {
+    pub label: String,
+    pub fractions: Vec<(Integer, Integer, Integer, Integer)>,
+    pub terms: usize,
+    pub max_den: Integer,
+    pub bits: u64,
+}
+
+impl Candidate {
+    fn score(&self, metric: Metric) -> (usize, Integer, u64) {
+        match metric {
+            Metric::Terms => (self.terms, Integer::new(), 0),
+            Metric::MaxDen => (0, self.max_den.clone(), 0),
+            Metric::Bits => (0, Integer::new(), self.bits),
+            Metric::Lex => (self.terms, self.max_den.clone(), self.bits),
+        }
+    }
+}
+
+/// Configurations tried by --best-of: limits 2, 8 and denominator - 1 (capped),
+/// each without merging and with every merge strategy (greedy both directions)
+/// Subset merging costs O(3^n) in the number of terms, so it runs only at limit 2, whose expansion is the shortest
+fn configurations(den: &Integer, args: &Args) -> Vec<(String, Args)> {
+    let mut limits = vec![2, 8];
+    let unsplit = den.to_usize().map_or(MAX_PORTFOLIO_LIMIT, |d| d.saturating_sub(1).min(MAX_PORTFOLIO_LIMIT));
+    if unsplit > 8 {
+        limits.push(unsplit);
+    }
+    let mut ret = vec![];
+    for (k, &limit) in limits.iter().enumerate() {
+        let base = Args { limit, raw: false, merge: false, reverse: false, ..args.clone() };
+        ret.push((format!("--limit {}", limit), base.clone()));
+        for (name, strategy, reverse) in [
+            ("greedy", MergeStrategy::Greedy, false),
+            ("greedy --reverse", MergeStrategy::Greedy, true),
+            ("optimal", MergeStrategy::Optimal, false),
+            ("subset", MergeStrategy::Subset, false),
+        ].into_iter().filter(|(_, strategy, _)| k == 0 || *strategy != MergeStrategy::Subset) {
+            let config = Args { merge: true, merge_strategy: strategy, reverse, ..base.clone() };
+            ret.push((format!("--limit {} --merge {}", limit, name), config));
+        }
+    }
+    ret
+}
+
+/// Run every configuration in parallel and return all candidates with the index of the best
+/// cf: certified CF quotients for irrational input
+pub fn best_of(num: &Integer, den: &Integer, cf: Option<&[Integer]>, metric: Metric, args: &Args) -> (Vec<Candidate>, usize) {
+    let configs = configurations(den, args);
+    let candidates = thread::scope(|scope| {
+        let handles = configs.iter().map(|(label, config)| {
+            scope.spawn(move || {
+                let fractions = match cf {
+                    Some(cf) => as_egyptian_fraction_irrational(cf, config),
+                    None => as_egyptian_fraction(num, den, config),
+                };
+                let max_den = fractions.iter().map(|f| f.1.clone()).max().unwrap_or_default();
+                let bits = fractions.iter().map(|f| f.1.significant_bits() as u64).sum();
+                Candidate { label: label.clone(), terms: fractions.len(), fractions, max_den, bits }
+            })
+        }).collect::<Vec<_>>();
+        handles.into_iter().map(|h| h.join().unwrap()).collect::<Vec<_>>()
+    });
+    let best = (0..candidates.len())
+        .min_by_key(|&i| candidates[i].score(metric))
+        .unwrap_or(0);
+    (candidates, best)
+}
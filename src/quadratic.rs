@@ -0,0 +1,346 @@
+//! Quadratic irrationals a + b√d: exact arithmetic and periodic continued fractions (PQa)
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use rug::{Integer, Rational};
+
+use crate::interval::Interval;
+
+/// Trial divisors used to pull square factors out of a radicand
+const SQUARE_FACTOR_BOUND: u32 = 1000;
+
+/// a + b√d with d > 1 not a perfect square
+/// b may be 0 only for intermediate values; see `Value` for the demotion to rationals
+#[derive(Debug, Clone)]
+pub struct Quad {
+    pub a: Rational,
+    pub b: Rational,
+    pub d: Integer,
+}
+
+impl Quad {
+    /// √r for a positive rational r that is not a perfect square
+    pub fn sqrt(r: &Rational) -> Quad {
+        // √(n/m) = √(n·m) / m
+        let d = Integer::from(r.numer() * r.denom());
+        let (mut b, mut d) = (Rational::from((Integer::from(1), r.denom().clone())), d);
+        for k in 2..=SQUARE_FACTOR_BOUND {
+            let k2 = Integer::from(k * k);
+            while d.is_divisible(&k2) {
+                d /= &k2;
+                b *= k;
+            }
+        }
+        Quad { a: Rational::new(), b, d }
+    }
+
+    /// Rational r viewed in Q(√d)
+    pub fn rational(r: Rational, d: &Integer) -> Quad {
+        Quad { a: r, b: Rational::new(), d: d.clone() }
+    }
+
+    pub fn is_rational(&self) -> bool {
+        self.b.is_zero()
+    }
+
+    pub fn add(&self, o: &Quad) -> Quad {
+        Quad { a: Rational::from(&self.a + &o.a), b: Rational::from(&self.b + &o.b), d: self.d.clone() }
+    }
+
+    pub fn sub(&self, o: &Quad) -> Quad {
+        Quad { a: Rational::from(&self.a - &o.a), b: Rational::from(&self.b - &o.b), d: self.d.clone() }
+    }
+
+    pub fn mul(&self, o: &Quad) -> Quad {
+        let a = Rational::from(&self.a * &o.a) + Rational::from(&self.b * &o.b) * &self.d;
+        let b = Rational::from(&self.a * &o.b) + Rational::from(&self.b * &o.a);
+        Quad { a, b, d: self.d.clone() }
+    }
+
+    /// x / y = x·ȳ / N(y), where N(y) = y.a² - y.b²·d vanishes only for y = 0
+    pub fn div(&self, o: &Quad) -> Result<Quad, String> {
+        let norm = Rational::from(o.a.square_ref()) - Rational::from(o.b.square_ref()) * &o.d;
+        if norm.is_zero() {
+            return Err("division by zero".to_string());
+        }
+        let conjugate = Quad { a: o.a.clone(), b: -o.b.clone(), d: o.d.clone() };
+        let x = self.mul(&conjugate);
+        Ok(Quad { a: x.a / &norm, b: x.b / &norm, d: x.d })
+    }
+
+    pub fn pow(&self, n: u32) -> Quad {
+        let mut ret = Quad::rational(Rational::from(1), &self.d);
+        for _ in 0..n {
+            ret = ret.mul(self);
+        }
+        ret
+    }
+
+    pub fn to_interval(&self, precision: u32) -> Interval {
+        let root = Interval::exact(&Rational::from(&self.d), precision).sqrt().unwrap();
+        Interval::exact(&self.a, precision).add(&Interval::exact(&self.b, precision).mul(&root))
+    }
+
+    /// Write as (P + √D)/Q with integers and Q | D - P², as the PQa recurrence requires
+    pub fn pqa_form(&self) -> (Integer, Integer, Integer) {
+        let c = self.a.denom().clone().lcm(self.b.denom());
+        let a = Integer::from(self.a.numer() * &c) / self.a.denom();
+        let b = Integer::from(self.b.numer() * &c) / self.b.denom();
+        let mut d = b.clone().square() * &self.d;
+        let (mut p, mut q) = if b > 0 { (a, c) } else { (-a, -c) };
+        if !Integer::from(&d - p.square_ref()).is_divisible(&q) {
+            let scale = q.clone().abs();
+            p *= &scale;
+            d *= scale.clone().square();
+            q *= scale;
+        }
+        (p, d, q)
+    }
+
+    /// a + b√d < 0: compare a² with b²·d when the signs of a and b differ
+    pub fn is_negative(&self) -> bool {
+        if (self.a < 0) == (self.b < 0) || self.a.is_zero() {
+            return self.b < 0;
+        }
+        let a2 = Rational::from(self.a.square_ref());
+        let b2d = Rational::from(self.b.square_ref()) * &self.d;
+        if a2 > b2d { self.a < 0 } else { self.b < 0 }
+    }
+
+    pub fn abs(&self) -> Quad {
+        if self.is_negative() {
+            Quad { a: -self.a.clone(), b: -self.b.clone(), d: self.d.clone() }
+        } else {
+            self.clone()
+        }
+    }
+
+    /// Periodic regular CF, starting with a_0 < 0 for negative values
+    pub fn periodic_cf(&self) -> PeriodicCf {
+        let (p, d, q) = self.pqa_form();
+        pqa(p, d, q)
+    }
+}
+
+impl std::fmt::Display for Quad {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} + {}·√{}", self.a, self.b, self.d)
+    }
+}
+
+/// PQa: a_i = ⌊(P_i + √D)/Q_i⌋, P_{i+1} = a_i·Q_i - P_i, Q_{i+1} = (D - P_{i+1}²)/Q_i
+/// The CF is periodic from the first repeated state (P_i, Q_i)
+fn pqa(mut p: Integer, d: Integer, mut q: Integer) -> PeriodicCf {
+    let s = d.clone().sqrt();
+    let mut seen = HashMap::new();
+    let mut quotients = vec![];
+    loop {
+        if let Some(&start) = seen.get(&(p.clone(), q.clone())) {
+            let period = quotients.split_off(start);
+            return PeriodicCf::new(quotients, period);
+        }
+        seen.insert((p.clone(), q.clone()), quotients.len());
+        // for Q < 0, ⌊(P + √D)/Q⌋ = ⌊(P + ⌊√D⌋ + 1)/Q⌋ since √D is irrational
+        let top = if q > 0 { Integer::from(&p + &s) } else { Integer::from(&p + &s) + 1 };
+        let a = top.div_rem_floor(q.clone()).0;
+        p = Integer::from(&a * &q) - &p;
+        q = (Integer::from(&d - p.square_ref())) / &q;
+        quotients.push(a);
+    }
+}
+
+/// Convergent of √D as (q, p, p² - D·q²)
+pub type PellRow = (Integer, Integer, Integer);
+
+/// Pell equation p² - D·q² = 1 from the exact CF of √D, in integers only: rows for the convergents
+/// up to the fundamental solution, the last convergent of the first period (of the second one when
+/// the period length is odd, the first then ending in a norm -1 solution); None unless D > 0 is not a square
+pub fn pell(d: &Integer) -> Option<(PeriodicCf, Vec<PellRow>)> {
+    if *d <= 0 || d.is_perfect_square() {
+        return None;
+    }
+    let cf = pqa(Integer::new(), d.clone(), Integer::from(1));
+    let len = cf.period.len();
+    let n = if len.is_multiple_of(2) { len } else { 2 * len };
+    // (p_{k-1}, p_{k-2}) and (q_{k-1}, q_{k-2}), from p_{-1}/q_{-1} = 1/0 and p_{-2}/q_{-2} = 0/1
+    let (mut p, mut p1) = (Integer::from(1), Integer::new());
+    let (mut q, mut q1) = (Integer::new(), Integer::from(1));
+    let mut rows = vec![];
+    for a in cf.take(n) {
+        (p, p1) = (Integer::from(&a * &p) + &p1, p);
+        (q, q1) = (Integer::from(&a * &q) + &q1, q);
+        let norm = Integer::from(p.square_ref()) - Integer::from(q.square_ref()) * d;
+        rows.push((q.clone(), p.clone(), norm));
+    }
+    Some((cf, rows))
+}
+
+/// p + q√D as (p, q)
+pub type QuadInt = (Integer, Integer);
+
+/// (p + q√D)·(t + u√D)
+fn times(x: &QuadInt, unit: &QuadInt, d: &Integer) -> QuadInt {
+    let p = Integer::from(&x.0 * &unit.0) + Integer::from(&x.1 * &unit.1) * d;
+    let q = Integer::from(&x.0 * &unit.1) + Integer::from(&x.1 * &unit.0);
+    (p, q)
+}
+
+/// Sign chosen so that p > 0 (or q > 0 when p = 0); -x is in the same class as x
+fn positive(x: QuadInt) -> QuadInt {
+    if x.0 < 0 || (x.0 == 0 && x.1 < 0) { (-x.0, -x.1) } else { x }
+}
+
+/// Generalised Pell equation p² - D·q² = N for N ≠ 0: the fundamental solution (p, q) of each class,
+/// with the smallest |q| in its class, and the fundamental unit (t, u) that generates each class.
+/// LMM (Lagrange, Matthews, Mollin): for each f² | N, m = N/f² and z² ≡ D (mod |m|) with
+/// -|m|/2 < z ≤ |m|/2, the PQa expansion of (z + √D)/|m| reaches Q_i = ±1 within its first period
+/// exactly when the class exists, and then G_{i-1}² - D·B_{i-1}² = ±m
+/// None unless D > 0 is not a square
+pub fn pell_classes(d: &Integer, n: &Integer) -> Option<(Vec<QuadInt>, QuadInt)> {
+    let (_, rows) = pell(d)?;
+    let (u, t, _) = rows.last().unwrap();
+    let unit = (t.clone(), u.clone());
+    let unit_inverse = (t.clone(), -u.clone());
+    // a solution of p² - D·q² = -1 turns a solution of -m into one of m
+    let quasi = rows.iter().find(|(_, _, norm)| *norm == -1).map(|(q, p, _)| (p.clone(), q.clone()));
+    let s = d.clone().sqrt();
+    let mut classes = vec![];
+    let mut f = Integer::from(1);
+    while Integer::from(f.square_ref()) <= *n.as_abs() {
+        let f2 = Integer::from(f.square_ref());
+        if n.is_divisible(&f2) {
+            let m = Integer::from(n / &f2);
+            let ma = m.clone().abs();
+            let mut z = -(Integer::from(&ma - 1u32) / 2u32);
+            while z <= Integer::from(&ma / 2) {
+                if (Integer::from(z.square_ref()) - d).is_divisible(&ma) {
+                    if let Some(x) = lmm(&z, &ma, d, &s) {
+                        let norm = Integer::from(x.0.square_ref()) - Integer::from(x.1.square_ref()) * d;
+                        let x = if norm == m { Some(x) } else { quasi.as_ref().map(|r| times(&x, r, d)) };
+                        if let Some((p, q)) = x {
+                            classes.push((p * &f, q * &f));
+                        }
+                    }
+                }
+                z += 1;
+            }
+        }
+        f += 1;
+    }
+    // move each solution to the smallest |q| in its class (|q| falls, then rises along the class)
+    let smaller = |a: &QuadInt, b: &QuadInt| {
+        a.1.cmp_abs(&b.1).then(b.1.cmp(&a.1)) == Ordering::Less
+    };
+    let mut fundamental = vec![];
+    for x in classes {
+        let mut x = positive(x);
+        loop {
+            let up = positive(times(&x, &unit, d));
+            let down = positive(times(&x, &unit_inverse, d));
+            if smaller(&up, &x) {
+                x = up;
+            } else if smaller(&down, &x) {
+                x = down;
+            } else {
+                break;
+            }
+        }
+        if !fundamental.contains(&x) {
+            fundamental.push(x);
+        }
+    }
+    fundamental.sort_by(|a, b| a.1.cmp_abs(&b.1).then(b.1.cmp(&a.1)));
+    Some((fundamental, unit))
+}
+
+/// Solutions of a class with |q| ≤ bound, from its fundamental solution, ordered by |q|
+pub fn pell_class_solutions(x: &QuadInt, unit: &QuadInt, d: &Integer, bound: &Integer)
+    -> Vec<QuadInt>
+{
+    let unit_inverse = (unit.0.clone(), -unit.1.clone());
+    let mut ret = vec![];
+    for (step, skip) in [(unit, 0), (&unit_inverse, 1)] {
+        let mut y = x.clone();
+        for _ in 0..skip {
+            y = positive(times(&y, step, d));
+        }
+        while y.1.cmp_abs(bound) != Ordering::Greater {
+            ret.push(y.clone());
+            y = positive(times(&y, step, d));
+        }
+    }
+    ret.sort_by(|a, b| a.1.cmp_abs(&b.1).then(b.1.cmp(&a.1)));
+    ret
+}
+
+/// PQa from (z + √D)/|m| until Q_i = ±1 (i ≥ 1), returning (G_{i-1}, B_{i-1}); None after a full period
+fn lmm(z: &Integer, ma: &Integer, d: &Integer, s: &Integer) -> Option<QuadInt> {
+    let (mut p, mut q) = (z.clone(), ma.clone());
+    let (mut g, mut g1) = (q.clone(), -p.clone());
+    let (mut b, mut b1) = (Integer::new(), Integer::from(1));
+    let mut seen = HashSet::new();
+    for i in 0.. {
+        if i >= 1 && *q.as_abs() == 1 {
+            return Some((g, b));
+        }
+        if !seen.insert((p.clone(), q.clone())) {
+            return None;
+        }
+        // as in pqa: ⌊(P + √D)/Q⌋ = ⌊(P + ⌊√D⌋ + 1)/Q⌋ for Q < 0
+        let top = if q > 0 { Integer::from(&p + s) } else { Integer::from(&p + s) + 1 };
+        let a = top.div_rem_floor(q.clone()).0;
+        (g, g1) = (Integer::from(&a * &g) + &g1, g);
+        (b, b1) = (Integer::from(&a * &b) + &b1, b);
+        p = Integer::from(&a * &q) - &p;
+        q = (Integer::from(d - p.square_ref())) / &q;
+    }
+    unreachable!()
+}
+
+/// Eventually periodic CF [a_0; a_1, ..., (a_s+1, ..., a_s+L)]
+#[derive(Debug, Clone)]
+pub struct PeriodicCf {
+    /// Pre-period, always starting with a_0
+    pub pre: Vec<Integer>,
+    pub period: Vec<Integer>,
+}
+
+impl PeriodicCf {
+    fn new(mut pre: Vec<Integer>, mut period: Vec<Integer>) -> PeriodicCf {
+        if pre.is_empty() {
+            // purely periodic: show a_0 and rotate, e.g. [(1)] as [1; (1)]
+            pre.push(period[0].clone());
+            period.rotate_left(1);
+        }
+        PeriodicCf { pre, period }
+    }
+
+    /// First n quotients
+    pub fn take(&self, n: usize) -> Vec<Integer> {
+        self.pre.iter().chain(self.period.iter().cycle()).take(n).cloned().collect()
+    }
+
+    /// First tuple k whose j = a_{2k} lies in the period, and the period of j (L or L/2 quotients)
+    pub fn tuple_period(&self) -> (usize, usize) {
+        let start = self.pre.len().div_ceil(2).max(1);
+        let len = self.period.len();
+        (start, if len.is_multiple_of(2) { len / 2 } else { len })
+    }
+
+    /// Quotients covering the pre-period and one period of the tuples
+    pub fn tuple_cover(&self) -> usize {
+        let (start, len) = self.tuple_period();
+        2 * (start + len - 1) + 1
+    }
+}
+
+impl std::fmt::Display for PeriodicCf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let join = |xs: &[Integer]| xs.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(", ");
+        write!(f, "[{}; ", self.pre[0])?;
+        if self.pre.len() > 1 {
+            write!(f, "{}, ", join(&self.pre[1..]))?;
+        }
+        write!(f, "({})]", join(&self.period))
+    }
+}
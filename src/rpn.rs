@@ -1,170 +1,780 @@
-use std::convert::TryFrom;
-use std::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::OnceLock;
 use num_prime::nt_funcs::nth_prime;
-use rug::{Integer, Float, Rational};
+use rug::{Complete, Integer, Rational};
 use rug::float::Constant;
 use rug::ops::Pow;
 
-pub fn _parse_rpn(s: &str) -> Integer { // TODO error handling
-    let parts = s.split(" ")
-        .into_iter().map(|x| x.to_string()).collect::<Vec::<String>>();
-    let mut stack = Vec::<String>::new();
-    for el in parts.iter() {
-        if el == "^" || el == "-" || el == "+" || el == "*" {
-            let b = Integer::from_str(&stack.pop().unwrap()).unwrap();
-            let a = Integer::from_str(&stack.pop().unwrap()).unwrap();
-            let c = if el == "^" {
-                a.pow(&b.to_u32().unwrap())
-            } else if el == "+" {
-                a.add(&b)
-            } else if el == "-" {
-                a.sub(&b)
-            } else if el == "*" {
-                a.mul(&b)
-            } else {
-                Integer::from(0)
-            };
-            stack.push(c.to_string());
-        } else if el == "seq" {
-            let b = Integer::from_str(&stack.pop().unwrap()).unwrap().to_u64().unwrap();
-            let mut a = Integer::from_str(&stack.pop().unwrap()).unwrap().to_u64().unwrap();
-            while a <= b {
-                stack.push(a.clone().to_string());
-                a.add_assign(1);
-            }
-        } else if el == "sum" || el == "prod" || el == "lcm" {
-            let mut c = Integer::from(0);
-            if el == "sum" {
-                while !stack.is_empty() {
-                    let a = Integer::from_str(&stack.pop().unwrap()).unwrap();
-                    c.add_assign(&a);
-                }
-            } else if el == "prod" {
-                c.add_assign(1);
-                while !stack.is_empty() {
-                    let a = Integer::from_str(&stack.pop().unwrap()).unwrap();
-                    c.mul_assign(&a);
-                }
-            } else if el == "lcm" {
-                c.add_assign(1);
-                while !stack.is_empty() {
-                    let a = Integer::from_str(&stack.pop().unwrap()).unwrap();
-                    c.lcm_mut(&a);
+use crate::cf::{CfExpr, Pattern};
+use crate::interval::Interval;
+use crate::quadratic::Quad;
+
+/// Precision in bits used when an exact evaluation meets an inexact constant
+pub const DEFAULT_PRECISION: u32 = 256;
+
+/// User-defined words loaded by --defs, expanded in place during evaluation
+static DEFINITIONS: OnceLock<HashMap<String, Vec<String>>> = OnceLock::new();
+
+/// Word expansions allowed per evaluation, guarding against recursive definitions
+const MAX_EXPANSIONS: usize = 100_000;
+
+/// Value on the evaluation stack
+/// Promoted Integer → Rational → Quad → Interval only when an operation needs it,
+/// and exact results are demoted back to Integer when the denominator is 1
+/// Quad keeps square roots of rationals exact as a + b√d, and Stream keeps exact arithmetic
+/// across fields (√2 + √3) as an expression expanded through CF streams
+/// Inexact values are rigorous enclosures: MPFR bounds rounded outwards
+#[derive(Debug, Clone)]
+pub enum Value {
+    Integer(Integer),
+    Rational(Rational),
+    Quad(Quad),
+    Stream(CfExpr),
+    Interval(Interval),
+}
+
+impl Value {
+    fn exact(r: Rational) -> Value {
+        if r.denom() == &1 {
+            Value::Integer(r.into_numer_denom().0)
+        } else {
+            Value::Rational(r)
+        }
+    }
+
+    fn quad(q: Quad) -> Value {
+        if q.is_rational() {
+            Value::exact(q.a)
+        } else {
+            Value::Quad(q)
+        }
+    }
+
+    /// Exact rational value, or None for a Quad or an inexact Interval
+    pub fn to_rational(&self) -> Option<Rational> {
+        match self {
+            Value::Integer(n) => Some(Rational::from(n)),
+            Value::Rational(r) => Some(r.clone()),
+            Value::Quad(_) | Value::Stream(_) | Value::Interval(_) => None,
+        }
+    }
+
+    /// Enclosure of the value, a point interval (up to rounding) for exact values
+    pub fn to_interval(&self, precision: u32) -> Interval {
+        match self {
+            Value::Interval(x) => x.clone(),
+            Value::Quad(q) => q.to_interval(precision),
+            Value::Stream(expr) => expr.to_interval(precision),
+            _ => Interval::exact(&self.to_rational().unwrap(), precision),
+        }
+    }
+
+    pub fn to_integer(&self) -> Result<Integer, String> {
+        match self {
+            Value::Integer(n) => Ok(n.clone()),
+            _ => Err(format!("expected an integer, got {}", self)),
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Integer(n) => write!(f, "{}", n),
+            Value::Rational(r) => write!(f, "{}", r),
+            Value::Quad(q) => write!(f, "{}", q),
+            Value::Stream(expr) => write!(f, "{}", expr),
+            Value::Interval(x) => write!(f, "{}", x),
+        }
+    }
+}
+
+/// Binary arithmetic, exact whenever both operands are exact
+fn binary(op: &str, a: Value, b: Value, precision: u32) -> Result<Value, String> {
+    if op == "^" {
+        return power(a, b, precision);
+    }
+    Ok(match (a.to_rational(), b.to_rational()) {
+        (Some(_), Some(y)) if op == "/" && y.is_zero() => return Err("division by zero".to_string()),
+        (Some(x), Some(y)) => Value::exact(match op {
+            "+" => x + y,
+            "-" => x - y,
+            "*" => x * y,
+            _ => x / y,
+        }),
+        _ => {
+            if let Some((x, y)) = quads(&a, &b) {
+                return Ok(Value::quad(match op {
+                    "+" => x.add(&y),
+                    "-" => x.sub(&y),
+                    "*" => x.mul(&y),
+                    _ => x.div(&y)?,
+                }));
+            }
+            if let (Some(x), Some(y)) = (stream(&a), stream(&b)) {
+                if op == "/" && b.to_rational().is_some_and(|y| y.is_zero()) {
+                    return Err("division by zero".to_string());
                 }
+                return Ok(Value::Stream(CfExpr::Binary(op.chars().next().unwrap(), Box::new(x), Box::new(y))));
+            }
+            let (x, y) = (a.to_interval(precision), b.to_interval(precision));
+            Value::Interval(match op {
+                "+" => x.add(&y),
+                "-" => x.sub(&y),
+                "*" => x.mul(&y),
+                _ => x.div(&y)?,
+            })
+        }
+    })
+}
+
+/// Both operands in the same field Q(√d), if neither is an Interval and the radicands agree
+fn quads(a: &Value, b: &Value) -> Option<(Quad, Quad)> {
+    let d = match (a, b) {
+        (Value::Quad(x), Value::Quad(y)) if x.d != y.d => return None,
+        (Value::Quad(x), _) | (_, Value::Quad(x)) => x.d.clone(),
+        _ => return None,
+    };
+    let lift = |v: &Value| match v {
+        Value::Quad(q) => Some(q.clone()),
+        _ => v.to_rational().map(|r| Quad::rational(r, &d)),
+    };
+    Some((lift(a)?, lift(b)?))
+}
+
+/// Exact operand of a CF stream combination; None for intervals
+fn stream(v: &Value) -> Option<CfExpr> {
+    match v {
+        Value::Quad(q) => Some(CfExpr::Quad(q.clone())),
+        Value::Stream(expr) => Some(expr.clone()),
+        Value::Interval(_) => None,
+        _ => v.to_rational().map(CfExpr::Rational),
+    }
+}
+
+/// Exact n-th root of a rational, if numerator and denominator are perfect n-th powers
+fn exact_root(r: &Rational, n: u32) -> Option<Rational> {
+    if n == 0 || (*r < 0 && n.is_multiple_of(2)) {
+        return None;
+    }
+    let (num, den) = (r.numer(), r.denom());
+    let num_root = num.clone().root(n);
+    let den_root = den.clone().root(n);
+    if num_root.clone().pow(n) == *num && den_root.clone().pow(n) == *den {
+        Some(Rational::from((num_root, den_root)))
+    } else {
+        None
+    }
+}
+
+/// Square root, kept exact for perfect squares and as a Quad for other positive rationals
+fn sqrt(a: Value, precision: u32) -> Result<Value, String> {
+    Ok(match a.to_rational() {
+        Some(r) => match exact_root(&r, 2) {
+            Some(root) => Value::exact(root),
+            None if r > 0 => Value::Quad(Quad::sqrt(&r)),
+            None => Value::Interval(a.to_interval(precision).sqrt()?),
+        },
+        None => Value::Interval(a.to_interval(precision).sqrt()?),
+    })
+}
+
+/// a^b: exact for exact base and integer exponent, or rational exponent p/q
+/// when the base is a perfect q-th power; real power otherwise
+fn power(a: Value, b: Value, precision: u32) -> Result<Value, String> {
+    let negative = match &b {
+        Value::Integer(n) => *n < 0,
+        Value::Rational(y) => *y < 0,
+        _ => false,
+    };
+    if negative && a.to_rational().is_some_and(|x| x.is_zero()) {
+        return Err("division by zero".to_string());
+    }
+    if let (Some(x), Value::Rational(y)) = (a.to_rational(), &b) {
+        if let Some(root) = y.denom().to_u32().and_then(|q| exact_root(&x, q)) {
+            if let Some(p) = y.numer().to_i32() {
+                return Ok(Value::exact(root.pow(p)));
             }
-            stack.push(c.to_string());
-
-        } else if el == "!"  || el == "p" || el == "sqrt" || el == "np" || el == "pp" || el == "fib" {
-            let mut a = Integer::from_str(&stack.pop().unwrap()).unwrap();
-            let c = if el == "fib" {
-                // Fibonacci using fast doubling
-                fn fib(n: u64) -> (Integer, Integer) {
-                    if n == 0 {
-                        return (Integer::from(0), Integer::from(1));
-                    }
-                    let (a, b) = fib(n / 2);
-                    let c = &a * (Integer::from(2) * &b - &a);
-                    let d = a.clone() * &a + b.clone() * &b;
-                    if n % 2 == 0 {
-                        (c, d)
-                    } else {
-                        (d.clone(), c + d)
-                    }
+        }
+    }
+    let too_large = || format!("exponent {} too large", b);
+    Ok(match (&a, &b) {
+        (Value::Integer(x), Value::Integer(n)) if *n >= 0 => {
+            Value::Integer(x.clone().pow(n.to_u32().ok_or_else(too_large)?))
+        }
+        (Value::Integer(_) | Value::Rational(_), Value::Integer(n)) => {
+            Value::exact(a.to_rational().unwrap().pow(n.to_i32().ok_or_else(too_large)?))
+        }
+        (Value::Quad(x), Value::Integer(n)) => {
+            let p = x.pow(n.clone().abs().to_u32().ok_or_else(too_large)?);
+            Value::quad(if *n < 0 { Quad::rational(Rational::from(1), &x.d).div(&p)? } else { p })
+        }
+        (Value::Stream(x), Value::Integer(n)) => {
+            // square and multiply, so the stream tree grows with log n
+            let mut e = n.clone().abs().to_u32().ok_or_else(too_large)?;
+            let (mut base, mut acc) = (x.clone(), CfExpr::Rational(Rational::from(1)));
+            while e > 0 {
+                if e & 1 == 1 {
+                    acc = CfExpr::Binary('*', Box::new(acc), Box::new(base.clone()));
                 }
-                fib(a.to_u64().unwrap()).0.to_string()
-            } else if el == "!" {
-                let mut c = Integer::from(1);
-                while a > 1 {
-                    c.mul_assign(&a);
-                    a.sub_assign(Integer::from(1));
+                e >>= 1;
+                if e > 0 {
+                    base = CfExpr::Binary('*', Box::new(base.clone()), Box::new(base));
                 }
-                c.to_string()
-            } else if el == "p" {
-                nth_prime(a.to_u64().unwrap()).to_string()
-            } else if el == "np" {
-                a.next_prime().to_string()
-            } else if el == "pp" {
-                a.prev_prime().to_string()
-            } else if el == "sqrt" {
-                a.sqrt().to_string()
-            } else {
-                "0".to_string()
-            };
-            stack.push(c);
-        }  else {
-            stack.push(el.to_string());
+            }
+            Value::Stream(if *n < 0 { CfExpr::Binary('/', Box::new(CfExpr::Rational(Rational::from(1))), Box::new(acc)) } else { acc })
+        }
+        (Value::Interval(x), Value::Integer(n)) => Value::Interval(x.pow(n)?),
+        _ => Value::Interval(a.to_interval(precision).powf(&b.to_interval(precision))?),
+    })
+}
+
+/// n-th root, kept exact for perfect powers; odd roots of negative numbers are real
+fn root(a: Value, n: &Integer, precision: u32) -> Result<Value, String> {
+    let n = n.to_u32().filter(|&n| n > 0).ok_or_else(|| format!("root index {} must be a positive integer", n))?;
+    Ok(match a.to_rational().and_then(|r| exact_root(&r, n)) {
+        Some(r) => Value::exact(r),
+        None => Value::Interval(a.to_interval(precision).root(n)?),
+    })
+}
+
+/// Real functions, enclosed by MPFR bounds at the working precision;
+/// exp, tanh and tan of ±1/n are exact CF patterns
+fn function(name: &str, a: Value, precision: u32) -> Result<Value, String> {
+    let unit = a.to_rational().filter(|r| *r.numer().as_abs() == 1 && matches!(name, "exp" | "tanh" | "tan"));
+    if let Some(r) = unit {
+        let n = r.denom().clone();
+        let pattern = CfExpr::Pattern(match name {
+            "exp" => Pattern::Exp(n),
+            "tanh" => Pattern::Tanh(n),
+            _ => Pattern::Tan(n),
+        });
+        // e^(-x) = 1/e^x, tanh and tan are odd
+        let constant = |c: i32| Box::new(CfExpr::Rational(Rational::from(c)));
+        return Ok(Value::Stream(match (r < 0, name) {
+            (false, _) => pattern,
+            (true, "exp") => CfExpr::Binary('/', constant(1), Box::new(pattern)),
+            (true, _) => CfExpr::Binary('-', constant(0), Box::new(pattern)),
+        }));
+    }
+    let x = a.to_interval(precision);
+    Ok(Value::Interval(match name {
+        "log" => x.ln()?,
+        "exp" => x.exp(),
+        "tanh" => x.tanh(),
+        "tan" => x.tan()?,
+        "sin" => x.sin(),
+        "cos" => x.cos(),
+        "atan" => x.atan(),
+        "zeta" => x.zeta()?,
+        _ => x.gamma()?,
+    }))
+}
+
+/// Integer argument of a combinatorial function
+fn small(n: &Integer) -> Result<u32, String> {
+    n.to_u32().ok_or_else(|| format!("argument {} out of range", n))
+}
+
+/// Bernoulli number B_n (B_1 = -1/2) from B_m = -1/(m+1) sum_{k<m} binom(m+1, k) B_k
+fn bernoulli(n: u32) -> Rational {
+    let mut b: Vec<Rational> = vec![Rational::from(1)];
+    for m in 1..=n {
+        let mut sum = Rational::new();
+        for (k, bk) in b.iter().enumerate() {
+            if bk.is_zero() {
+                continue;
+            }
+            sum += Rational::from(Integer::binomial_u(m + 1, k as u32).complete()) * bk;
+        }
+        // odd Bernoulli numbers beyond B_1 vanish
+        b.push(if m > 1 && m % 2 == 1 { Rational::new() } else { -sum / (m + 1) });
+    }
+    b.pop().unwrap()
+}
+
+/// Harmonic sum 1/a + ... + 1/(b-1) by binary splitting
+fn harmonic(a: u32, b: u32) -> Rational {
+    if b <= a {
+        Rational::new()
+    } else if b - a == 1 {
+        Rational::from((1, a))
+    } else {
+        let mid = a + (b - a) / 2;
+        harmonic(a, mid) + harmonic(mid, b)
+    }
+}
+
+/// Fibonacci using fast doubling: returns (F(n), F(n+1))
+fn fib(n: u64) -> (Integer, Integer) {
+    if n == 0 {
+        return (Integer::from(0), Integer::from(1));
+    }
+    let (a, b) = fib(n / 2);
+    let c = &a * (Integer::from(2) * &b - &a);
+    let d = a.clone() * &a + b.clone() * &b;
+    if n.is_multiple_of(2) {
+        (c, d)
+    } else {
+        (d.clone(), c + d)
+    }
+}
+
+fn constant(name: &str, precision: u32) -> Option<Value> {
+    let point = |n: u32| Interval::exact(&Rational::from(n), precision);
+    let half = Rational::from((1, 2));
+    Some(Value::Interval(match name.to_lowercase().as_str() {
+        "pi" => Interval::constant(Constant::Pi, precision),
+        "e" => return Some(Value::Stream(CfExpr::Pattern(Pattern::Exp(Integer::from(1))))),
+        // Golden ratio: (1 + sqrt(5)) / 2
+        "phi" => return Some(Value::Quad(Quad { a: half.clone(), b: half, d: Integer::from(5) })),
+        "sqrt2" => return Some(Value::Quad(Quad::sqrt(&Rational::from(2)))),
+        // Euler-Mascheroni constant
+        "gamma" => Interval::constant(Constant::Euler, precision),
+        "ln2" => Interval::constant(Constant::Log2, precision),
+        "catalan" => Interval::constant(Constant::Catalan, precision),
+        // Apéry's constant ζ(3)
+        "zeta3" => point(3).zeta().ok()?,
+        _ => return None,
+    }))
+}
+
+/// Parse Forth-style definitions `: name body ;`; `#` starts a comment running to end of line
+pub fn parse_definitions(text: &str) -> Result<HashMap<String, Vec<String>>, String> {
+    let mut definitions = HashMap::new();
+    let mut tokens = text.lines()
+        .map(|line| line.split_once('#').map_or(line, |(code, _)| code))
+        .flat_map(str::split_whitespace);
+    while let Some(token) = tokens.next() {
+        if token != ":" {
+            return Err(format!("expected ':' to start a definition, found '{}'", token));
+        }
+        let name = match tokens.next() {
+            Some(name) if name != ";" => name.to_string(),
+            _ => return Err("missing name after ':'".to_string()),
+        };
+        let mut body = vec![];
+        loop {
+            match tokens.next() {
+                Some(";") => break,
+                Some(t) => body.push(t.to_string()),
+                None => return Err(format!("definition of '{}' is missing ';'", name)),
+            }
+        }
+        definitions.insert(name, body);
+    }
+    Ok(definitions)
+}
+
+/// Install the definitions used by every later evaluation (only the first call has an effect)
+pub fn load_definitions(text: &str) -> Result<(), String> {
+    let _ = DEFINITIONS.set(parse_definitions(text)?);
+    Ok(())
+}
+
+/// Largest |exponent| of a scientific literal, whose value would otherwise take unbounded memory
+const MAX_EXPONENT: i64 = 100_000;
+
+/// Exact value of a numeric literal: integer, fraction `22/7`, decimal `0.25`,
+/// repeating decimal `0.(142857)` or `0.1(6)`, scientific `1.5e-3`, or `0x`/`0o`/`0b` integer
+/// None when `el` is not a literal, an error when it is one out of range
+pub fn parse_number(el: &str) -> Option<Result<Rational, String>> {
+    if let Ok(r) = Rational::from_str(el) {
+        return Some(Ok(r));
+    }
+    let (negative, body) = match el.strip_prefix('-') {
+        Some(body) => (true, body),
+        None => (false, el),
+    };
+    let value = match radix_prefix(body) {
+        Some((radix, digits)) if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_alphanumeric()) => {
+            Rational::from(Integer::from_str_radix(digits, radix).ok()?)
+        }
+        Some(_) => return None,
+        None => match decimal(body)? {
+            Ok(value) => value,
+            Err(e) => return Some(Err(e)),
+        },
+    };
+    Some(Ok(if negative { -value } else { value }))
+}
+
+fn radix_prefix(s: &str) -> Option<(i32, &str)> {
+    match s.get(..2)?.to_lowercase().as_str() {
+        "0x" => Some((16, &s[2..])),
+        "0o" => Some((8, &s[2..])),
+        "0b" => Some((2, &s[2..])),
+        _ => None,
+    }
+}
+
+/// Unsigned decimal `int.fixed(repeating)e±exp`, every part optional but some digit present
+fn decimal(s: &str) -> Option<Result<Rational, String>> {
+    let (mantissa, exponent) = match s.find(['e', 'E']) {
+        Some(k) => (&s[..k], s[k + 1..].parse::<i64>().ok()?),
+        None => (s, 0),
+    };
+    let (int_part, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let (fixed, repeating) = match frac.strip_suffix(')') {
+        Some(f) => f.split_once('(')?,
+        None => (frac, ""),
+    };
+    let parts = [int_part, fixed, repeating];
+    if parts.iter().all(|p| p.is_empty()) || !parts.iter().all(|p| p.chars().all(|c| c.is_ascii_digit())) {
+        return None;
+    }
+    let digits = |p: &str| if p.is_empty() { Integer::new() } else { Integer::from_str(p).unwrap() };
+    let scale = Integer::from(10).pow(fixed.len() as u32);
+    let mut value = Rational::from(digits(int_part)) + Rational::from((digits(fixed), scale.clone()));
+    if !repeating.is_empty() {
+        // 0.(d₁…dₖ) = d₁…dₖ / (10ᵏ - 1), shifted past the fixed digits
+        let period = Integer::from(10).pow(repeating.len() as u32) - 1;
+        value += Rational::from((digits(repeating), scale * period));
+    }
+    if exponent.abs() > MAX_EXPONENT {
+        return Some(Err(format!("literal out of range, the exponent must be within ±{}", MAX_EXPONENT)));
+    }
+    Some(Ok(value * Rational::from(10).pow(exponent as i32)))
+}
+
+/// Evaluation failure, located at the offending token
+#[derive(Debug, Clone)]
+pub struct EvalError {
+    pub message: String,
+    pub token: String,
+    /// 1-based column of the token in the expression
+    pub column: usize,
+    /// Stack depth when the token was reached
+    pub depth: usize,
+    /// Closest known word for an unknown token
+    pub suggestion: Option<String>,
+    expression: String,
+}
+
+impl std::fmt::Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.token.is_empty() {
+            write!(f, "'{}': ", self.token)?;
+        }
+        write!(f, "{} (column {}, stack depth {})", self.message, self.column, self.depth)?;
+        if let Some(word) = &self.suggestion {
+            write!(f, "; did you mean '{}'?", word)?;
         }
+        write!(f, "\n  {}\n  {}^", self.expression, " ".repeat(self.column - 1))
+    }
+}
+
+impl EvalError {
+    /// The same error located in `source`, the infix expression the RPN was compiled from,
+    /// where `columns` holds the source column of each RPN token
+    pub fn in_source(self, source: &str, columns: &[usize]) -> EvalError {
+        let rpn_columns = self.expression.split(' ').scan(1, |column, t| {
+            let start = *column;
+            *column += t.chars().count() + 1;
+            Some((t.is_empty(), start))
+        }).filter(|&(empty, _)| !empty).map(|(_, start)| start);
+        let column = rpn_columns.zip(columns).find(|&(start, _)| start == self.column)
+            .map_or(source.chars().count() + 1, |(_, &column)| column);
+        EvalError { column, expression: source.to_string(), ..self }
+    }
+}
+
+/// Built-in words, offered as suggestions for unknown tokens
+const WORDS: &[&str] = &[
+    "inv", "sqrt", "cbrt", "root", "isqrt", "log", "exp", "sin", "cos", "tan", "tanh", "atan", "zeta",
+    "gammafn",
+    "seq", "sum", "prod", "lcm", "gcd", "mod", "div", "binom", "powmod", "primorial", "catnum",
+    "lucas", "fib", "np", "pp", "bernoulli", "harmonic", "numer", "denom", "dup", "swap", "drop",
+    "over", "pi", "e", "phi", "sqrt2", "gamma", "ln2", "catalan", "zeta3",
+];
+
+/// Edit distance counting insertions, deletions, substitutions and adjacent transpositions
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b) = (a.chars().collect::<Vec<_>>(), b.chars().collect::<Vec<_>>());
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Closest known word within two edits, if any
+fn suggest<'a>(token: &str, candidates: impl Iterator<Item = &'a str>) -> Option<String> {
+    let token = token.to_lowercase();
+    candidates
+        .map(|w| (edit_distance(&token, &w.to_lowercase()), w))
+        .filter(|&(d, _)| d <= 2 && d < token.chars().count())
+        .min_by_key(|&(d, _)| d)
+        .map(|(_, w)| w.to_string())
+}
+
+/// A variable name is an identifier (a letter or '_', then letters, digits or '_') that doesn't
+/// shadow a built-in word, a constant or a --defs word; otherwise the error and a name to use instead
+fn check_variable(name: &str) -> Result<(), (String, Option<String>)> {
+    let identifier = name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    if !name.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        let suggestion = identifier.then(|| format!("_{}", name));
+        return Err(("variable name must start with a letter or '_'".to_string(), suggestion));
+    }
+    if !identifier {
+        return Err(("variable name must only contain letters, digits and '_'".to_string(), None));
+    }
+    let builtin = WORDS.contains(&name) || matches!(name, "p" | "H") || constant(name, 2).is_some();
+    if builtin || DEFINITIONS.get().is_some_and(|d| d.contains_key(name)) {
+        let kind = if builtin { "built-in word" } else { "defined word" };
+        return Err((format!("cannot assign to the {} '{}'", kind, name), Some(format!("{}_", name))));
+    }
+    Ok(())
+}
+
+fn pop(stack: &mut Vec<Value>) -> Result<Value, String> {
+    stack.pop().ok_or_else(|| "stack underflow".to_string())
+}
+
+/// Evaluate an RPN expression over Integer, Rational and Interval values
+/// Operators: + - * / ^ inv sqrt cbrt root isqrt seq sum prod lcm gcd
+/// Stack: dup swap drop over; `[` marks the floor for the next sum, prod, lcm or gcd
+/// (otherwise they fold the whole stack), `]` only closes the group visually
+/// Variables: `x =` pops into x, later `x` pushes it; words from --defs expand in place
+/// Number theory: mod div binom powmod ! !! primorial catnum lucas fib p np pp
+/// bernoulli, harmonic (alias H) as exact rationals; numer and denom extract parts
+/// Functions: log exp sin cos atan zeta gammafn
+/// Constants: pi, e, phi (golden ratio), sqrt2, gamma (Euler-Mascheroni),
+/// ln2, catalan (Catalan's G), zeta3 (Apéry's ζ(3))
+/// precision: number of bits for interval bounds
+/// Errors name the token, its column and the stack depth; the expression must leave one value
+pub fn evaluate(s: &str, precision: u32) -> Result<Value, EvalError> {
+    let mut stack = Vec::<Value>::new();
+    // tokens still to run with their columns, in reverse so user-defined words can be
+    // expanded in place (expanded tokens report the column of the word)
+    let mut pending = s.split(' ').scan(1, |column, t| {
+        let token = (t.to_string(), *column);
+        *column += t.chars().count() + 1;
+        Some(token)
+    }).filter(|(t, _)| !t.is_empty()).collect::<Vec<_>>();
+    pending.reverse();
+    let mut variables = HashMap::<String, Value>::new();
+    // stack depths at each open '[', the floor for the next aggregate
+    let mut marks = Vec::<usize>::new();
+    let mut expansions = 0;
+    let fail = |message: String, token: &str, column: usize, depth: usize, suggestion: Option<String>| EvalError {
+        message, token: token.to_string(), column, depth, suggestion, expression: s.to_string(),
     };
-    Integer::from_str(&stack.pop().unwrap()).unwrap()
-}
-
-/// Parse RPN expression with irrational constants, returning (numerator, denominator)
-/// Supports: pi, e, phi (golden ratio), sqrt2, gamma (Euler-Mascheroni)
-/// precision: number of bits for Float computation
-pub fn _parse_rpn_irrational(s: &str, precision: u32) -> (Integer, Integer) {
-    let parts = s.split(" ")
-        .into_iter().map(|x| x.to_string()).collect::<Vec<String>>();
-    let mut stack = Vec::<Float>::new();
-
-    for el in parts.iter() {
-        let el_lower = el.to_lowercase();
-
-        // Constants
-        if el_lower == "pi" {
-            stack.push(Float::with_val(precision, Constant::Pi));
-        } else if el_lower == "e" {
-            stack.push(Float::with_val(precision, 1).exp());
-        } else if el_lower == "phi" {
-            // Golden ratio: (1 + sqrt(5)) / 2
-            let sqrt5 = Float::with_val(precision, 5).sqrt();
-            stack.push((Float::with_val(precision, 1) + sqrt5) / 2);
-        } else if el_lower == "sqrt2" {
-            stack.push(Float::with_val(precision, 2).sqrt());
-        } else if el_lower == "gamma" {
-            // Euler-Mascheroni constant
-            stack.push(Float::with_val(precision, Constant::Euler));
-        } else if el == "^" || el == "-" || el == "+" || el == "*" || el == "/" {
-            let b = stack.pop().unwrap();
-            let a = stack.pop().unwrap();
-            let c = if el == "^" {
-                a.pow(b.to_u32_saturating().unwrap_or(1))
-            } else if el == "+" {
-                a + b
-            } else if el == "-" {
-                a - b
-            } else if el == "*" {
-                a * b
-            } else if el == "/" {
-                a / b
+    while let Some((token, column)) = pending.pop() {
+        let el = token.as_str();
+        let depth = stack.len();
+        if pending.last().is_some_and(|(next, _)| next == "=") {
+            pending.pop();
+            if let Err((message, suggestion)) = check_variable(el) {
+                return Err(fail(message, el, column, depth, suggestion));
+            }
+            let value = stack.pop().ok_or_else(|| fail("nothing to assign to".to_string(), el, column, depth, None))?;
+            variables.insert(token, value);
+            continue;
+        }
+        if let Some(value) = variables.get(el) {
+            stack.push(value.clone());
+            continue;
+        }
+        if let Some(body) = DEFINITIONS.get().and_then(|d| d.get(el)) {
+            expansions += 1;
+            if expansions > MAX_EXPANSIONS {
+                return Err(fail("too many expansions, recursive definition?".to_string(), el, column, depth, None));
+            }
+            pending.extend(body.iter().rev().map(|t| (t.clone(), column)));
+            continue;
+        }
+        match step(el, &mut stack, &mut marks, precision) {
+            Ok(true) => {}
+            Ok(false) => {
+                let known = WORDS.iter().copied()
+                    .chain(variables.keys().map(String::as_str))
+                    .chain(DEFINITIONS.get().into_iter().flat_map(|d| d.keys().map(String::as_str)));
+                return Err(fail("unknown token".to_string(), el, column, depth, suggest(el, known)));
+            }
+            Err(message) => return Err(fail(message, el, column, depth, None)),
+        }
+        if let Some(Value::Interval(x)) = stack.last() {
+            if !x.is_finite() {
+                return Err(fail(format!("result {} is not finite", x), el, column, depth, None));
+            }
+        }
+    }
+    let end = s.chars().count() + 1;
+    match stack.len() {
+        0 => Err(fail("empty expression".to_string(), "", end, 0, None)),
+        1 => Ok(stack.pop().unwrap()),
+        n => Err(fail(format!("{} values left on the stack, expected 1", n), "", end, n, None)),
+    }
+}
+
+/// Apply one built-in word or literal to the stack; Ok(false) if the token is unknown
+fn step(el: &str, stack: &mut Vec<Value>, marks: &mut Vec<usize>, precision: u32) -> Result<bool, String> {
+    match el {
+        "+" | "-" | "*" | "/" | "^" => {
+            let b = pop(stack)?;
+            let a = pop(stack)?;
+            stack.push(binary(el, a, b, precision)?);
+        }
+        "inv" => {
+            let a = pop(stack)?;
+            stack.push(binary("/", Value::Integer(Integer::from(1)), a, precision)?);
+        }
+        "sqrt" => {
+            let a = pop(stack)?;
+            stack.push(sqrt(a, precision)?);
+        }
+        "cbrt" => {
+            let a = pop(stack)?;
+            stack.push(root(a, &Integer::from(3), precision)?);
+        }
+        "root" => {
+            let n = pop(stack)?.to_integer()?;
+            let a = pop(stack)?;
+            stack.push(root(a, &n, precision)?);
+        }
+        "log" | "exp" | "sin" | "cos" | "tan" | "tanh" | "atan" | "zeta" | "gammafn" => {
+            let a = pop(stack)?;
+            stack.push(function(el, a, precision)?);
+        }
+        "seq" => {
+            let b = pop(stack)?.to_integer()?;
+            let mut a = pop(stack)?.to_integer()?;
+            while a <= b {
+                stack.push(Value::Integer(a.clone()));
+                a += 1;
+            }
+        }
+        "dup" => {
+            let a = stack.last().ok_or("stack underflow")?.clone();
+            stack.push(a);
+        }
+        "drop" => {
+            pop(stack)?;
+        }
+        "swap" | "over" => {
+            let n = stack.len();
+            if n < 2 {
+                return Err("stack underflow".to_string());
+            }
+            if el == "swap" {
+                stack.swap(n - 1, n - 2);
             } else {
-                Float::with_val(precision, 0)
-            };
+                stack.push(stack[n - 2].clone());
+            }
+        }
+        "[" => marks.push(stack.len()),
+        "]" => {}
+        "sum" | "prod" => {
+            let (op, unit) = if el == "sum" { ("+", 0) } else { ("*", 1) };
+            let floor = marks.pop().unwrap_or(0).min(stack.len());
+            let mut c = Value::Integer(Integer::from(unit));
+            for a in stack.split_off(floor).into_iter().rev() {
+                c = binary(op, c, a, precision)?;
+            }
             stack.push(c);
-        } else if el == "sqrt" {
-            let a = stack.pop().unwrap();
-            stack.push(a.sqrt());
-        } else if el == "inv" {
-            let a = stack.pop().unwrap();
-            stack.push(Float::with_val(precision, 1) / a);
-        } else {
-            // Try parsing as number
-            if let Ok(n) = el.parse::<i64>() {
-                stack.push(Float::with_val(precision, n));
-            } else if let Ok(f) = el.parse::<f64>() {
-                stack.push(Float::with_val(precision, f));
+        }
+        "lcm" | "gcd" => {
+            let floor = marks.pop().unwrap_or(0).min(stack.len());
+            let mut c = Integer::from(if el == "lcm" { 1 } else { 0 });
+            for a in stack.split_off(floor).into_iter().rev() {
+                if el == "lcm" {
+                    c.lcm_mut(&a.to_integer()?);
+                } else {
+                    c.gcd_mut(&a.to_integer()?);
+                }
+            }
+            stack.push(Value::Integer(c));
+        }
+        "mod" | "div" | "binom" => {
+            let b = pop(stack)?.to_integer()?;
+            let a = pop(stack)?.to_integer()?;
+            if el != "binom" && b.is_zero() {
+                return Err("division by zero".to_string());
+            }
+            stack.push(Value::Integer(match el {
+                "mod" => a.modulo(&b),
+                "div" => a.div_rem_floor(b).0,
+                _ => a.binomial(small(&b)?),
+            }));
+        }
+        "powmod" => {
+            let m = pop(stack)?.to_integer()?;
+            let e = pop(stack)?.to_integer()?;
+            let a = pop(stack)?.to_integer()?;
+            if m.is_zero() {
+                return Err("division by zero".to_string());
+            }
+            stack.push(Value::Integer(a.pow_mod(&e, &m).map_err(|_| "base not invertible".to_string())?));
+        }
+        "numer" | "denom" => {
+            let a = pop(stack)?;
+            let (n, d) = a.to_rational().ok_or_else(|| format!("expected an exact value, got {}", a))?.into_numer_denom();
+            stack.push(Value::Integer(if el == "numer" { n } else { d }));
+        }
+        "bernoulli" | "harmonic" | "H" => {
+            let n = small(&pop(stack)?.to_integer()?)?;
+            stack.push(Value::exact(if el == "bernoulli" { bernoulli(n) } else { harmonic(1, n + 1) }));
+        }
+        "!" | "!!" | "primorial" | "catnum" | "lucas" | "p" | "np" | "pp" | "fib" | "isqrt" => {
+            let a = pop(stack)?.to_integer()?;
+            let index = || a.to_u64().ok_or_else(|| format!("argument {} out of range", a));
+            stack.push(Value::Integer(match el {
+                "fib" => fib(index()?).0,
+                "!" => Integer::factorial(small(&a)?).complete(),
+                "!!" => Integer::factorial_2(small(&a)?).complete(),
+                "primorial" => Integer::primorial(small(&a)?).complete(),
+                // C_n = binom(2n, n) / (n + 1)
+                "catnum" => Integer::from(2 * &a).binomial(small(&a)?) / (a + 1),
+                "lucas" => Integer::lucas(small(&a)?).complete(),
+                "p" if a < 1 => return Err(format!("prime index must be ≥ 1, got {}", a)),
+                "p" => Integer::from(nth_prime(index()?)),
+                "np" => a.next_prime(),
+                "pp" if a <= 2 => return Err(format!("no prime below {}", a)),
+                "pp" => a.prev_prime(),
+                _ if a < 0 => return Err(format!("square root of negative {}", a)),
+                _ => a.sqrt(),
+            }));
+        }
+        _ => {
+            if let Some(c) = constant(el, precision) {
+                stack.push(c);
+            } else if let Some(r) = parse_number(el) {
+                stack.push(Value::exact(r?));
             } else {
-                eprintln!("Warning: unknown token '{}', using 0", el);
-                stack.push(Float::with_val(precision, 0));
+                return Ok(false);
             }
         }
     }
+    Ok(true)
+}
 
-    let result = stack.pop().unwrap_or(Float::with_val(precision, 0));
+/// A / B as a quadratic irrational, if the quotient lies in some Q(√d) but not in Q
+pub fn quadratic_quotient(a: &Value, b: &Value) -> Option<Quad> {
+    match binary("/", a.clone(), b.clone(), DEFAULT_PRECISION) {
+        Ok(Value::Quad(q)) => Some(q),
+        _ => None,
+    }
+}
 
-    // Convert to Rational preserving full precision (not via f64!)
-    let rational = Rational::try_from(&result).unwrap_or(Rational::from(0));
-    let (num, den) = rational.into_numer_denom();
+/// Parse RPN expression that must evaluate exactly
+pub fn _parse_rpn(s: &str) -> Result<Rational, String> {
+    let value = evaluate(s, DEFAULT_PRECISION).map_err(|e| e.to_string())?;
+    value.to_rational().ok_or_else(|| format!("expected an exact value, got {}", value))
+}
 
-    (num, den)
-}
\ No newline at end of file
+/// Parse RPN expression that must evaluate to an integer
+pub fn _parse_rpn_integer(s: &str) -> Result<Integer, String> {
+    evaluate(s, DEFAULT_PRECISION).map_err(|e| e.to_string())?.to_integer()
+}
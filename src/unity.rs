@@ -0,0 +1,179 @@
+use clap::ValueEnum;
+use num_prime::nt_funcs::factorize128;
+use rug::{Integer, Rational};
+
+use crate::{as_egyptian_fraction, Args, DEFAULT_LIMIT};
+
+/// Structured representations of 1
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Family {
+    /// Sylvester's sequence: 1/2 + 1/3 + 1/7 + ... + 1/(s_{n+1} - 1)
+    Sylvester,
+    /// Znám problem: sum 1/x_i + 1/prod x_i = 1 with k terms x_i
+    Znam,
+    /// Divisor sum of a perfect number N: sum_{d|N, d>1} 1/d = 1
+    Perfect,
+}
+
+/// First n terms of Sylvester's sequence: s_1 = 2, s_{k+1} = s_k² - s_k + 1
+pub fn sylvester(n: usize) -> Vec<Integer> {
+    let mut s = vec![];
+    let mut x = Integer::from(2);
+    for _ in 0..n {
+        let next = x.clone().square() - &x + 1;
+        s.push(x);
+        x = next;
+    }
+    s
+}
+
+/// 1 = 1/s_1 + ... + 1/s_n + 1/(s_{n+1} - 1); the first n terms alone sum to 1 - 1/(s_{n+1} - 1)
+pub fn sylvester_unity(n: usize) -> Vec<Integer> {
+    let mut s = sylvester(n + 1);
+    let last = s.pop().unwrap() - 1;
+    s.push(last);
+    s
+}
+
+/// All increasing k-tuples with sum 1/x_i + 1/prod x_i = 1
+/// Returns (x_1..x_k, proper) where proper means no x_i = prod_{j≠i} x_j + 1,
+/// i.e. each x_i is a proper divisor of prod_{j≠i} x_j + 1 (Znám problem)
+pub fn znam(k: usize) -> Vec<(Vec<Integer>, bool)> {
+    let mut ret = vec![];
+    if k >= 2 {
+        znam_search(&Rational::from(1), &Integer::from(1), k, &Integer::from(2), &mut vec![], &mut ret);
+    }
+    ret
+}
+
+/// Depth-first search: with remainder r, product P and t terms left,
+/// the next term x satisfies 1/r < x < (t + 1)/r; the last term is forced
+/// by 1/x + 1/(P·x) = r, i.e. x = (P + 1)/(P·r)
+fn znam_search(rem: &Rational, prod: &Integer, terms_left: usize, min: &Integer,
+               path: &mut Vec<Integer>, ret: &mut Vec<(Vec<Integer>, bool)>) {
+    if terms_left == 1 {
+        let x = Rational::from(prod.clone() + 1) / (rem.clone() * prod);
+        if x.denom() == &1 && x.numer() >= min {
+            path.push(x.numer().clone());
+            let proper = is_proper_znam(path);
+            ret.push((path.clone(), proper));
+            path.pop();
+        }
+        return;
+    }
+    let mut x = Integer::from(rem.denom() / rem.numer()) + 1;
+    if x < *min {
+        x = min.clone();
+    }
+    let upper = Integer::from(rem.denom() * (terms_left + 1)) / rem.numer();
+    while x < upper {
+        let next = rem.clone() - Rational::from((Integer::from(1), x.clone()));
+        if next > 0 {
+            path.push(x.clone());
+            znam_search(&next, &(prod.clone() * &x), terms_left - 1, &(x.clone() + 1), path, ret);
+            path.pop();
+        }
+        x += 1;
+    }
+}
+
+fn is_proper_znam(xs: &[Integer]) -> bool {
+    let prod = xs.iter().fold(Integer::from(1), |acc, x| acc * x);
+    xs.iter().all(|x| Integer::from(&prod / x) + 1 != *x)
+}
+
+/// Znám's divisibility property: every x_i divides prod_{j≠i} x_j + 1
+fn is_znam(xs: &[Integer]) -> bool {
+    let prod = xs.iter().fold(Integer::from(1), |acc, x| acc * x);
+    xs.iter().all(|x| {
+        let rest: Integer = Integer::from(&prod / x) + 1;
+        rest.is_divisible(x)
+    })
+}
+
+/// Divisors d > 1 of n in increasing order
+pub fn divisors(n: &Integer) -> Result<Vec<Integer>, String> {
+    let n = n.to_u128().filter(|&n| n > 0).ok_or_else(|| format!("perfect: N = {} must be positive and below 2^128", n))?;
+    let mut divs = vec![1u128];
+    for (p, e) in factorize128(n) {
+        let current = divs.clone();
+        let mut pk = 1u128;
+        for _ in 0..e {
+            pk *= p;
+            divs.extend(current.iter().map(|d| d * pk));
+        }
+    }
+    divs.sort();
+    Ok(divs.into_iter().skip(1).map(Integer::from).collect())
+}
+
+/// Exact sum of unit fractions 1/d
+pub fn unit_sum(dens: &[Integer]) -> Rational {
+    dens.iter().fold(Rational::from(0), |acc, d| acc + Rational::from((Integer::from(1), d.clone())))
+}
+
+/// Cross-check a sum other than 1 (a Sylvester prefix, a divisor sum) by feeding it back through the
+/// Egyptian fraction decomposition and summing the resulting terms again; always as unit fractions,
+/// whatever --raw, --merge or --limit say
+pub fn cross_check(sum: &Rational, args: &Args) -> bool {
+    let (num, den) = (sum.numer().clone(), sum.denom().clone());
+    let args = Args { raw: false, bisect: false, merge: false, limit: DEFAULT_LIMIT, ..args.clone() };
+    let decomposition = as_egyptian_fraction(&num, &den, &args);
+    let total = decomposition.iter().fold(Rational::from(0), |acc, (a, b, _, _)| {
+        acc + Rational::from((a.clone(), b.clone()))
+    });
+    total == *sum
+}
+
+/// Generate the requested family, print it and verify every sum
+/// Returns false if any representation fails to verify, an error if n is out of range
+pub fn run(family: Family, n: &Integer, args: &Args) -> Result<bool, String> {
+    let mut ok = true;
+    let print = |dens: &[Integer], note: &str| {
+        if !args.silent {
+            let line = dens.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(" ");
+            if note.is_empty() {
+                println!("{}", line);
+            } else {
+                println!("{}\t{}", line, note);
+            }
+        }
+    };
+    match family {
+        Family::Sylvester => {
+            let n = n.to_usize().ok_or_else(|| format!("sylvester: n = {} out of range", n))?;
+            let dens = sylvester_unity(n);
+            print(&dens, "");
+            ok &= unit_sum(&dens) == 1;
+            // Every prefix s_1..s_m sums to 1 - 1/(s_{m+1} - 1)
+            let s = sylvester(n + 1);
+            for m in 1..=n {
+                let prefix = unit_sum(&s[..m]);
+                let expected = 1 - Rational::from((Integer::from(1), s[m].clone() - 1));
+                ok &= prefix == expected && cross_check(&prefix, args);
+            }
+        }
+        Family::Znam => {
+            let k = n.to_usize().filter(|&k| k >= 2).ok_or_else(|| format!("znam: k = {} out of range, it must be at least 2", n))?;
+            for (xs, proper) in znam(k) {
+                let prod = xs.iter().fold(Integer::from(1), |acc, x| acc * x);
+                let mut dens = xs.clone();
+                dens.push(prod);
+                print(&dens, if proper { "proper" } else { "improper" });
+                ok &= unit_sum(&dens) == 1 && is_znam(&xs);
+            }
+        }
+        Family::Perfect => {
+            let dens = divisors(n)?;
+            let sum = unit_sum(&dens);
+            if sum == 1 {
+                print(&dens, "");
+            } else {
+                print(&dens, &format!("sum {}", sum));
+                eprintln!("# {} is not perfect: divisor sum = {}", n, sum);
+            }
+            ok &= sum == 1 || cross_check(&sum, args);
+        }
+    }
+    Ok(ok)
+}